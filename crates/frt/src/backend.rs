@@ -1,25 +1,35 @@
 use anyhow::Result;
-use libfrt::{backend::Backend, profile::Profile};
+use libfrt::backend::registry::BackendRegistry;
 
-#[cfg(feature = "backend-www")]
-use libfrt_backend_www::BackendWWW;
+#[cfg(feature = "backend-null")]
+use libfrt::backend::registry::BackendRegistration;
+
+/// Registry of the backends compiled in
+pub fn registry() -> Result<BackendRegistry> {
+    // not mutated if no backend is enabled
+    #[allow(unused_mut)]
+    let mut registry = BackendRegistry::default();
 
-#[derive(clap::ValueEnum, Clone, Debug)]
-pub enum CliBackend {
     #[cfg(feature = "backend-www")]
-    WWW,
+    registry.register(libfrt_backend_www::registration())?;
+
+    #[cfg(feature = "backend-json")]
+    registry.register(libfrt_backend_json::registration())?;
+
+    #[cfg(feature = "backend-gemini")]
+    registry.register(libfrt_backend_gemini::registration())?;
+
+    #[cfg(feature = "backend-markdown")]
+    registry.register(libfrt_backend_markdown::registration())?;
+
+    #[cfg(feature = "backend-sqlite")]
+    registry.register(libfrt_backend_sqlite::registration())?;
+
+    #[cfg(feature = "backend-csv")]
+    registry.register(libfrt_backend_csv::registration())?;
+
     #[cfg(feature = "backend-null")]
-    Null,
-}
+    registry.register(BackendRegistration::null())?;
 
-impl CliBackend {
-    pub fn new_backend(&self, profile: &mut Profile) -> Result<Option<Box<dyn Backend>>> {
-        Ok(match self {
-            CliBackend::WWW => {
-                let v = profile.backends.remove("www");
-                Some(Box::new(BackendWWW::new(v)?))
-            }
-            CliBackend::Null => None,
-        })
-    }
+    Ok(registry)
 }
@@ -0,0 +1,32 @@
+use anyhow::Result;
+
+use clap::Parser;
+use libfrt::profile::Profile;
+
+use crate::backend;
+
+#[derive(Parser, Debug)]
+pub struct SubCommandBackends {}
+
+pub fn cli(profile: Profile, _sub_args: &SubCommandBackends) -> Result<()> {
+    let registry = backend::registry()?;
+
+    println!("Available backends:");
+    for registration in registry.iter() {
+        print!("  {registration}");
+    }
+
+    println!();
+    println!("Build backends: {}", profile.build_backends.join(", "));
+
+    let configured = registry.describe_profile(&profile)?;
+    if !configured.is_empty() {
+        println!();
+        println!("Configured in profile:");
+        for line in configured.lines() {
+            println!("  {line}");
+        }
+    }
+
+    Ok(())
+}
@@ -1,37 +1,196 @@
+use std::path::Path;
+
 use anyhow::Result;
 
 use clap::Parser;
-use libfrt::backend::BackendArguments;
+use libfrt::backend::{BackendArguments, BackendInstance};
 use libfrt::{profile::Profile, Context};
 
-use crate::CliBackend;
+use crate::backend;
 
 #[derive(Parser, Debug)]
 pub struct SubCommandBuild {
-    /// Extra arguments passed to render backend.
+    /// Extra arguments passed to render backends, in KEY=VALUE format.
+    /// Use BACKEND:KEY=VALUE to pass to a single backend.
+    /// Values true and false are passed as booleans, others as strings
     #[clap(short = 'a', long = "argument")]
     args: Vec<String>,
 
     /// Directory for file-system output of full build.
-    /// Same as use -a output=...
+    /// Each backend writes to a subdirectory named after it if several
+    /// backends are selected, override with -a BACKEND:output=...
     #[clap(short = 'o', long, default_value = "output")]
     output: String,
 }
 
-pub fn cli(mut profile: Profile, sub_args: &SubCommandBuild, backend: &CliBackend) -> Result<()> {
-    let backend = backend.new_backend(&mut profile)?;
+/// Split `[BACKEND:]KEY=VALUE`
+fn parse_argument(arg: &str) -> Result<(Option<&str>, &str, &str)> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| libfrt::err!(InvalidArgument, "Invalid backend argument: '{}'", arg))?;
 
-    let mut context = Context::new(profile, backend)?;
-    context.full_init()?;
+    Ok(match key.split_once(':') {
+        Some((backend, key)) => (Some(backend), key, value),
+        None => (None, key, value),
+    })
+}
+
+/// Pass `[BACKEND:]KEY=VALUE` arguments to all instances, or to the one
+/// named by the prefix
+fn route_arguments(instances: &mut [BackendInstance], args: &[String]) -> Result<()> {
+    for arg in args.iter() {
+        let (backend, key, value) = parse_argument(arg)?;
+
+        if let Some(name) = backend {
+            if !instances.iter().any(|i| i.name == name) {
+                libfrt::bail!(
+                    InvalidArgument,
+                    "Argument '{}' is for backend '{}', which is not selected",
+                    arg,
+                    name
+                )
+            }
+        }
+
+        for instance in instances
+            .iter_mut()
+            .filter(|i| backend.is_none() || backend == Some(i.name.as_str()))
+        {
+            match value {
+                "true" | "false" => instance.args.set_bool(key.to_owned(), value == "true"),
+                _ => instance.args.set_string(key.to_owned(), value.to_owned()),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn cli(profile: Profile, sub_args: &SubCommandBuild, backends: &[String]) -> Result<()> {
+    let registry = backend::registry()?;
+
+    let backends = match backends.is_empty() {
+        true => &profile.build_backends,
+        false => backends,
+    };
+
+    for (i, name) in backends.iter().enumerate() {
+        if backends[..i].contains(name) {
+            libfrt::bail!(InvalidArgument, "Backend '{}' selected twice", name)
+        }
+    }
+
+    let mut instances = Vec::new();
+    for resolved in registry.resolve_selected(backends, &profile)? {
+        let name = resolved.name.clone();
+        if let Some(b) = resolved.create()? {
+            instances.push(BackendInstance {
+                name,
+                backend: b,
+                args: BackendArguments::default(),
+            });
+        }
+    }
+
+    let shared_output = instances.len() == 1;
+    for instance in instances.iter_mut() {
+        let output = match shared_output {
+            true => sub_args.output.clone(),
+            false => Path::new(&sub_args.output)
+                .join(&instance.name)
+                .display()
+                .to_string(),
+        };
 
-    let mut backend_args = BackendArguments::default();
+        instance.args.set_bool("fs_output".to_owned(), true);
+        instance.args.set_string("output".to_owned(), output);
+    }
 
-    backend_args.set_bool("fs_output".to_owned(), true);
-    backend_args.set_string("output".to_owned(), sub_args.output.clone());
+    route_arguments(&mut instances, &sub_args.args)?;
 
-    context.resync_backend(&backend_args)?;
+    let mut context = Context::new(profile, instances)?;
+    context.full_init()?;
+
+    context.resync_backends()?;
 
-    context.invoke_backend()?;
+    context.invoke_backends()?;
+
+    if !context.has_backend() {
+        print!("{}", context.data().stats());
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use libfrt::backend::{Backend, BackendArguments, BackendInstance};
+    use libfrt::profile::Profile;
+    use libfrt::ContextData;
+
+    use super::{parse_argument, route_arguments};
+
+    struct Dummy;
+
+    impl Backend for Dummy {
+        fn render(&self, _profile: &Profile, _data: &ContextData) -> Result<BackendArguments> {
+            Ok(BackendArguments::default())
+        }
+    }
+
+    fn instances(names: &[&str]) -> Vec<BackendInstance> {
+        names
+            .iter()
+            .map(|name| BackendInstance {
+                name: name.to_string(),
+                backend: Box::new(Dummy),
+                args: BackendArguments::default(),
+            })
+            .collect()
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_argument() {
+        assert_eq!(parse_argument("k=v").unwrap(), (None, "k", "v"));
+        assert_eq!(parse_argument("k=a=b").unwrap(), (None, "k", "a=b"));
+        assert_eq!(
+            parse_argument("json:k=a:b").unwrap(),
+            (Some("json"), "k", "a:b")
+        );
+        assert!(parse_argument("k").is_err());
+    }
+
+    #[test]
+    fn test_route_arguments() {
+        let mut instances = instances(&["www", "json"]);
+        route_arguments(
+            &mut instances,
+            &args(&["prefix=/games", "json:pretty=true", "www:pretty=no"]),
+        )
+        .unwrap();
+
+        for instance in instances.iter() {
+            assert_eq!(
+                instance.args.get_string("prefix").as_deref(),
+                Some("/games")
+            );
+        }
+        assert!(instances[1].args.get_bool("pretty"));
+        assert_eq!(
+            instances[0].args.get_string("pretty").as_deref(),
+            Some("no")
+        );
+        assert!(!instances[0].args.get_bool("pretty"));
+    }
+
+    #[test]
+    fn test_route_unselected() {
+        let mut instances = instances(&["www"]);
+        assert!(route_arguments(&mut instances, &args(&["json:pretty=true"])).is_err());
+    }
+}
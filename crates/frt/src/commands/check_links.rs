@@ -0,0 +1,70 @@
+use anyhow::Result;
+
+use clap::Parser;
+use libfrt::linkcheck::{self, LinkCheckCache, LinkCheckOptions};
+use libfrt::{profile::Profile, Context};
+
+#[derive(Parser, Debug)]
+pub struct SubCommandCheckLinks {
+    /// Max number of concurrent requests
+    #[clap(short = 'j', long, default_value_t = 8)]
+    jobs: usize,
+
+    /// Timeout of a single request, in seconds
+    #[clap(long, default_value_t = 20)]
+    timeout: u64,
+
+    /// Replace URL prefix before checking, in FROM=TO format.
+    /// e.g. https://store.steampowered.com=http://127.0.0.1:8080
+    #[clap(long = "base-url")]
+    base_urls: Vec<String>,
+
+    /// Cache file of check results
+    #[clap(long, default_value = ".frt-linkcheck.json")]
+    cache: String,
+
+    /// Reuse cached successful results younger than this, in hours.
+    /// Use 0 to check all links again
+    #[clap(long, default_value_t = 72)]
+    max_age: i64,
+
+    /// Write report to file instead of stdout
+    #[clap(short = 'o', long)]
+    output: Option<String>,
+}
+
+pub fn cli(profile: Profile, sub_args: &SubCommandCheckLinks) -> Result<()> {
+    let mut options = LinkCheckOptions {
+        jobs: sub_args.jobs,
+        timeout: sub_args.timeout,
+        max_age: sub_args.max_age * 3600,
+        ..Default::default()
+    };
+
+    for base_url in sub_args.base_urls.iter() {
+        let (from, to) = base_url.split_once('=').ok_or_else(|| {
+            libfrt::err!(InvalidArgument, "Invalid base URL override: '{}'", base_url)
+        })?;
+        options.base_urls.push((from.to_owned(), to.to_owned()));
+    }
+
+    let mut context = Context::new(profile, Vec::new())?;
+    context.full_init()?;
+
+    let links = linkcheck::collect_links(context.data());
+
+    let mut cache = LinkCheckCache::load(&sub_args.cache)?;
+    let report = linkcheck::check_links(links, &options, &mut cache);
+    cache.save(&sub_args.cache)?;
+
+    match &sub_args.output {
+        Some(output) => std::fs::write(output, report.to_string())?,
+        None => print!("{report}"),
+    }
+
+    if report.broken() > 0 {
+        libfrt::bail!(Other, "{} broken links found", report.broken())
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use clap::Parser;
+use libfrt::import;
+use libfrt::{profile::Profile, Context};
+
+#[derive(Parser, Debug)]
+pub struct SubCommandImport {
+    /// Root of the old game database, containing `games/` and `assets/`
+    src: String,
+
+    /// Directory to create game bundles in.
+    /// Defaults to the first `path_games` of the profile
+    #[clap(short = 'o', long)]
+    output: Option<String>,
+
+    /// Also write the migration report to this file
+    #[clap(short = 'r', long)]
+    report: Option<String>,
+
+    /// Replace bundles which already exist
+    #[clap(long, default_value_t = false)]
+    overwrite: bool,
+}
+
+pub fn cli(profile: Profile, sub_args: &SubCommandImport) -> Result<()> {
+    let output = match &sub_args.output {
+        Some(output) => output.clone(),
+        None => profile
+            .path_games
+            .first()
+            .cloned()
+            .ok_or_else(|| libfrt::err!(InvalidArgument, "No games path in profile"))?,
+    };
+
+    let mut context = Context::new(profile, Vec::new())?;
+    context.init()?;
+
+    let notes = import::import_games(
+        &context.data().link_rules,
+        Path::new(&sub_args.src),
+        Path::new(&output),
+        sub_args.overwrite,
+    )?;
+
+    let report: String = notes.iter().map(|n| format!("{n}\n")).collect();
+    print!("{report}");
+
+    if let Some(path) = &sub_args.report {
+        std::fs::write(path, &report)?;
+    }
+
+    info!("{} migration notes", notes.len());
+
+    Ok(())
+}
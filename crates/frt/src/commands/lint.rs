@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use clap::Parser;
+use libfrt::entries::link::HintPolicy;
+use libfrt::lint;
+use libfrt::{profile::Profile, Context};
+
+#[derive(Parser, Debug)]
+pub struct SubCommandLint {
+    /// Lint a single game bundle instead of all games
+    game_bundle: Option<String>,
+
+    /// Report every unknown or mismatched stock link hint instead of
+    /// stopping at the first one
+    #[clap(long, default_value_t = false)]
+    migration: bool,
+}
+
+pub fn cli(mut profile: Profile, sub_args: &SubCommandLint) -> Result<()> {
+    if sub_args.migration {
+        profile.stock_link_hints = HintPolicy::Migration;
+    }
+
+    let mut context = Context::new(profile, Vec::new())?;
+
+    match &sub_args.game_bundle {
+        Some(path) => {
+            context.init()?;
+            context.load_game_bundle(Path::new(path))?;
+        }
+        None => context.full_init()?,
+    }
+
+    let messages = lint::lint_games(context.data());
+
+    for message in messages.iter() {
+        println!("{message}");
+    }
+
+    info!("{} lint messages", messages.len());
+
+    Ok(())
+}
@@ -1 +1,7 @@
-pub mod build;
\ No newline at end of file
+pub mod backends;
+pub mod build;
+pub mod check_links;
+pub mod import;
+pub mod lint;
+pub mod new;
+pub mod resolve_link;
\ No newline at end of file
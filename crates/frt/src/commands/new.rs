@@ -0,0 +1,139 @@
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use anyhow::Result;
+
+use clap::{Parser, Subcommand};
+use libfrt::scaffold::{self, GameTemplate};
+use libfrt::{profile::Profile, Context};
+
+#[derive(Parser, Debug)]
+pub struct SubCommandNew {
+    #[clap(subcommand)]
+    kind: NewKind,
+}
+
+#[derive(Subcommand, Debug)]
+enum NewKind {
+    /// Create a game bundle with a commented game.yaml
+    Game(NewGame),
+}
+
+#[derive(Parser, Debug)]
+struct NewGame {
+    /// Id of the game, used as bundle directory name
+    id: String,
+
+    /// Ask for the values below on the terminal
+    #[clap(short = 'i', long, default_value_t = false)]
+    interactive: bool,
+
+    /// Directory to create the bundle in.
+    /// Defaults to the first `path_games` of the profile
+    #[clap(short = 'o', long)]
+    output: Option<String>,
+
+    /// Name of the game, defaults to the id
+    #[clap(long)]
+    name: Option<String>,
+
+    #[clap(long)]
+    brief_description: Option<String>,
+
+    /// Image file to copy into the bundle as thumbnail
+    #[clap(long)]
+    thumbnail: Option<String>,
+
+    /// Store page or other URL, turned into a stock link if a rule
+    /// recognizes it. Can be given multiple times
+    #[clap(short = 'l', long = "link")]
+    links: Vec<String>,
+}
+
+/// Read a line after printing `prompt`, `None` if it is empty
+fn prompt(prompt: &str) -> Result<Option<String>> {
+    print!("{prompt}: ");
+    std::io::stdout().flush()?;
+
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+
+    let line = line.trim();
+    Ok(match line.is_empty() {
+        true => None,
+        false => Some(line.to_owned()),
+    })
+}
+
+fn new_game(profile: Profile, args: &NewGame) -> Result<()> {
+    scaffold::check_game_id(&args.id, &profile.path_games)?;
+
+    let output = match &args.output {
+        Some(output) => output.clone(),
+        None => profile
+            .path_games
+            .first()
+            .cloned()
+            .ok_or_else(|| libfrt::err!(InvalidArgument, "No games path in profile"))?,
+    };
+
+    let mut context = Context::new(profile, Vec::new())?;
+    context.init()?;
+    let rules = &context.data().link_rules;
+
+    let mut name = args.name.clone();
+    let mut brief_description = args.brief_description.clone();
+    let mut thumbnail = args.thumbnail.clone();
+    let mut links = args.links.clone();
+
+    if args.interactive {
+        if name.is_none() {
+            name = prompt(&format!("Name [{}]", args.id))?;
+        }
+        if brief_description.is_none() {
+            brief_description = prompt("Brief description (optional)")?;
+        }
+        if thumbnail.is_none() {
+            thumbnail = prompt("Thumbnail image file (optional)")?;
+        }
+        println!("Store pages and other links, one per line, empty line to finish");
+        while let Some(link) = prompt("Link")? {
+            links.push(link);
+        }
+    }
+
+    if let Some(thumbnail) = &thumbnail {
+        if !Path::new(thumbnail).is_file() {
+            libfrt::bail!(NotExist, "Thumbnail '{}' not found", thumbnail)
+        }
+    }
+
+    let mut template = GameTemplate {
+        name: name.unwrap_or_else(|| args.id.clone()),
+        brief_description,
+        ..Default::default()
+    };
+
+    for link in links.iter() {
+        match template.add_link(rules, link)? {
+            Some(rule) => info!("Link '{link}' recognized by stock rule '{rule}'"),
+            None => warn!("No stock rule recognizes link '{link}', added as custom link"),
+        }
+    }
+
+    let bundle = template.create(
+        Path::new(&output),
+        &args.id,
+        thumbnail.as_deref().map(Path::new),
+    )?;
+
+    println!("Created '{}'", bundle.join("game.yaml").display());
+
+    Ok(())
+}
+
+pub fn cli(profile: Profile, sub_args: &SubCommandNew) -> Result<()> {
+    match &sub_args.kind {
+        NewKind::Game(args) => new_game(profile, args),
+    }
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+use clap::Parser;
+use libfrt::{profile::Profile, Context};
+
+#[derive(Parser, Debug)]
+pub struct SubCommandResolveLink {
+    /// Link as written in game.yaml, e.g. steam:123 or a full URL
+    uri: String,
+}
+
+pub fn cli(profile: Profile, sub_args: &SubCommandResolveLink) -> Result<()> {
+    let mut context = Context::new(profile, Vec::new())?;
+    context.init()?;
+
+    for line in context.data().link_rules.explain(&sub_args.uri) {
+        println!("{line}");
+    }
+
+    Ok(())
+}
@@ -9,14 +9,23 @@ use libfrt::profile::Profile;
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 
-use crate::backend::CliBackend;
 
 #[derive(Subcommand, Debug)]
 pub enum SubCommand {
     /// Render FGI website, pages, components, etc
     Build(commands::build::SubCommandBuild),
+    /// Check whether links of games are still reachable
+    CheckLinks(commands::check_links::SubCommandCheckLinks),
+    /// Show which stock link rule a link resolves to, and why
+    ResolveLink(commands::resolve_link::SubCommandResolveLink),
     /// Validate and check source(s)
-    Lint { game_bundle: Option<String> },
+    Lint(commands::lint::SubCommandLint),
+    /// List available backends and how they are configured
+    Backends(commands::backends::SubCommandBackends),
+    /// Import games of the old FGI game database into bundles
+    Import(commands::import::SubCommandImport),
+    /// Create new bundles from templates
+    New(commands::new::SubCommandNew),
 }
 
 #[derive(Parser, Debug)]
@@ -35,9 +44,11 @@ struct Cli {
     #[clap(long)]
     config: Option<String>,
 
-    /// Select backend
-    #[clap(short = 'b', long, default_value = "www")]
-    backend: CliBackend,
+    /// Select backend, by name or by key of a [backends.*] profile table.
+    /// Can be given multiple times to render with several backends in order.
+    /// Defaults to `build_backends` of the profile
+    #[clap(short = 'b', long)]
+    backend: Vec<String>,
 
     #[clap(subcommand)]
     command: SubCommand,
@@ -85,7 +96,24 @@ fn main() -> Result<()> {
         SubCommand::Build(s) => {
             commands::build::cli(profile, &s, &args.backend)?;
         }
-        SubCommand::Lint { .. } => todo!(),
+        SubCommand::CheckLinks(s) => {
+            commands::check_links::cli(profile, &s)?;
+        }
+        SubCommand::ResolveLink(s) => {
+            commands::resolve_link::cli(profile, &s)?;
+        }
+        SubCommand::Lint(s) => {
+            commands::lint::cli(profile, &s)?;
+        }
+        SubCommand::Backends(s) => {
+            commands::backends::cli(profile, &s)?;
+        }
+        SubCommand::Import(s) => {
+            commands::import::cli(profile, &s)?;
+        }
+        SubCommand::New(s) => {
+            commands::new::cli(profile, &s)?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,98 @@
+mod profile;
+mod tables;
+
+#[macro_use]
+extern crate log;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use libfrt::backend::registry::BackendRegistration;
+use libfrt::backend::{clean_output_dir, Backend, BackendArguments};
+use libfrt::i18n::LangId;
+use libfrt::profile::Profile;
+use libfrt::utils::fs::ensure_dir;
+use libfrt::ContextData;
+use profile::ProfileCSV;
+use tables::TableContext;
+
+/// Registration of the CSV backend
+pub fn registration() -> BackendRegistration {
+    BackendRegistration {
+        name: "csv",
+        description: "CSV or TSV spreadsheets of games, links, medias, authors and tags",
+        constructor: Some(|value| Ok(Box::new(BackendCSV::new(value)?))),
+        config: profile::CONFIG_KEYS,
+    }
+}
+
+/// Writes one spreadsheet per entity type, for editorial review:
+/// `games`, `links`, `medias`, `authors` and `tags`.
+pub struct BackendCSV {
+    pub profile: ProfileCSV,
+
+    output: Option<PathBuf>,
+    langs: Vec<LangId>,
+}
+
+impl BackendCSV {
+    pub fn new(value: Option<toml::Value>) -> Result<Self> {
+        Ok(Self {
+            profile: match value {
+                Some(value) => ProfileCSV::from_value(value)?,
+                None => ProfileCSV::default(),
+            },
+
+            output: None,
+            langs: Vec::new(),
+        })
+    }
+}
+
+impl Backend for BackendCSV {
+    fn resync(
+        &mut self,
+        _profile: &Profile,
+        data: &mut ContextData,
+        args: &BackendArguments,
+    ) -> Result<()> {
+        self.output = clean_output_dir(args)?;
+        self.langs = data
+            .langs()
+            .into_iter()
+            .filter(|l| *l != LangId::default())
+            .collect();
+
+        Ok(())
+    }
+
+    fn render(&self, _profile: &Profile, data: &ContextData) -> Result<BackendArguments> {
+        let Some(output_dir) = &self.output else {
+            libfrt::bail!(InvalidArgument, "CSV backend requires file-system output")
+        };
+
+        let tc = TableContext {
+            data,
+            profile: &self.profile,
+            langs: &self.langs,
+            games: data.exported_games(self.profile.include_unlisted),
+        };
+
+        for (name, table) in tc.tables() {
+            let path = output_dir.join(format!("{}.{}", name, self.profile.format.extension()));
+            info!("Write {} rows to '{}'", table.len() - 1, path.display());
+
+            ensure_dir(&path)?;
+            let mut writer = csv::WriterBuilder::new()
+                .delimiter(self.profile.format.delimiter())
+                .from_path(&path)?;
+            for row in table {
+                writer.write_record(row)?;
+            }
+            writer.flush()?;
+        }
+
+        Ok(BackendArguments::default())
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use libfrt::backend::registry::{BackendConfigKey, INCLUDE_UNLISTED};
+use serde::Deserialize;
+
+/// Keys of `ProfileCSV`, for `frt backends`
+pub const CONFIG_KEYS: &[BackendConfigKey] = &[
+    BackendConfigKey {
+        key: "format",
+        kind: "string",
+        default: "csv",
+        description: "'csv' for comma-separated or 'tsv' for tab-separated files",
+    },
+    BackendConfigKey {
+        key: "list_separator",
+        kind: "string",
+        default: "; ",
+        description: "Separator of multi-valued fields, such as tags and roles",
+    },
+    INCLUDE_UNLISTED,
+];
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    Csv,
+    Tsv,
+}
+
+impl Format {
+    pub fn delimiter(&self) -> u8 {
+        match self {
+            Format::Csv => b',',
+            Format::Tsv => b'\t',
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Tsv => "tsv",
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ProfileCSV {
+    pub format: Format,
+
+    /// Separator of the values of multi-valued fields
+    pub list_separator: String,
+
+    /// See `ContextData::exported_games`
+    pub include_unlisted: bool,
+}
+
+impl Default for ProfileCSV {
+    fn default() -> Self {
+        Self {
+            format: Format::Csv,
+            list_separator: String::from("; "),
+            include_unlisted: true,
+        }
+    }
+}
+
+impl ProfileCSV {
+    pub fn from_value(value: toml::Value) -> Result<Self> {
+        Ok(ProfileCSV::deserialize(value)?)
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+
+use libfrt::entries::game::Game;
+use libfrt::entries::media::{Image, Media};
+use libfrt::i18n::LangId;
+use libfrt::utils::date::format_date;
+use libfrt::ContextData;
+
+use crate::profile::ProfileCSV;
+
+/// Rows of a single file, the first one is the header
+pub type Table = Vec<Vec<String>>;
+
+fn date(timestamp: Option<i64>) -> String {
+    timestamp.map(format_date).unwrap_or_default()
+}
+
+/// Builds the table of every entity type
+pub struct TableContext<'a> {
+    pub data: &'a ContextData,
+    pub profile: &'a ProfileCSV,
+    /// Languages other than the default one, which get a column each
+    pub langs: &'a [LangId],
+    /// Games to write, sorted by id
+    pub games: Vec<&'a Game>,
+}
+
+impl TableContext<'_> {
+    fn join<I, S>(&self, values: I) -> String
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        values
+            .into_iter()
+            .map(|s| s.as_ref().to_owned())
+            .collect::<Vec<_>>()
+            .join(&self.profile.list_separator)
+    }
+
+    /// All tables, with their file names without extension
+    pub fn tables(&self) -> Vec<(&'static str, Table)> {
+        vec![
+            ("games", self.games()),
+            ("links", self.links()),
+            ("medias", self.medias()),
+            ("authors", self.authors()),
+            ("tags", self.tags()),
+        ]
+    }
+
+    fn games(&self) -> Table {
+        let mut header: Vec<String> = vec!["id".into(), "state".into(), "name".into()];
+        header.extend(self.langs.iter().map(|l| format!("name.{}", l.as_str())));
+        header.extend(["tags", "links", "screenshots"].map(String::from));
+        header.extend(
+            self.langs
+                .iter()
+                .map(|l| format!("translated.{}", l.as_str())),
+        );
+        header.extend(["added", "updated"].map(String::from));
+
+        let mut table = vec![header];
+        for game in self.games.iter() {
+            let mut row = vec![
+                game.id.clone(),
+                game.state.as_str().to_owned(),
+                game.name.clone(),
+            ];
+            row.extend(self.langs.iter().map(|l| {
+                game.l10n
+                    .get(l)
+                    .and_then(|l10n| l10n.name.clone())
+                    .unwrap_or_default()
+            }));
+
+            let mut facets = game.facets();
+            facets.sort_unstable();
+            facets.dedup();
+            row.push(self.join(facets));
+
+            row.push(game.links.len().to_string());
+            row.push(game.medias.len().to_string());
+            row.extend(
+                self.langs
+                    .iter()
+                    .map(|l| game.l10n.contains_key(l).to_string()),
+            );
+            row.push(date(game.added));
+            row.push(date(game.updated));
+
+            table.push(row);
+        }
+
+        table
+    }
+
+    fn links(&self) -> Table {
+        let header = [
+            "game",
+            "position",
+            "rule",
+            "category",
+            "label",
+            "uri",
+            "patch_lang",
+        ];
+        let mut table = vec![header.map(String::from).to_vec()];
+
+        for game in self.games.iter() {
+            for (position, link) in game.links.iter().enumerate() {
+                table.push(vec![
+                    game.id.clone(),
+                    position.to_string(),
+                    link.rule
+                        .as_ref()
+                        .map(|r| r.name.clone())
+                        .unwrap_or_default(),
+                    link.category().as_str().to_owned(),
+                    link.localized_label(LangId::default()).to_owned(),
+                    link.uri.clone(),
+                    link.patch
+                        .as_ref()
+                        .map(|p| p.lang.clone())
+                        .unwrap_or_default(),
+                ]);
+            }
+        }
+
+        table
+    }
+
+    fn medias(&self) -> Table {
+        let header = [
+            "game",
+            "position",
+            "kind",
+            "sensitive",
+            "uris",
+            "caption",
+            "alt",
+        ];
+        let mut table = vec![header.map(String::from).to_vec()];
+
+        let caption = |images: &[&Image]| {
+            self.join(
+                images
+                    .iter()
+                    .filter_map(|i| i.caption.get(LangId::default())),
+            )
+        };
+        let alt = |images: &[&Image]| {
+            self.join(images.iter().filter_map(|i| i.alt.get(LangId::default())))
+        };
+
+        for game in self.games.iter() {
+            for (position, gm) in game.medias.iter().enumerate() {
+                let (kind, uris, images): (&str, Vec<&str>, Vec<&Image>) = match &gm.media {
+                    Media::Image(image) => ("image", vec![image.uri()], vec![image]),
+                    Media::HBox(images) => (
+                        "hbox",
+                        images.iter().map(Image::uri).collect(),
+                        images.iter().collect(),
+                    ),
+                    Media::Youtube(id) => ("youtube", vec![id.as_str()], Vec::new()),
+                    Media::Video { sources, .. } => (
+                        "video",
+                        sources.iter().map(|s| s.uri.as_str()).collect(),
+                        Vec::new(),
+                    ),
+                };
+
+                table.push(vec![
+                    game.id.clone(),
+                    position.to_string(),
+                    kind.to_owned(),
+                    gm.sensitive.to_string(),
+                    self.join(uris),
+                    caption(&images),
+                    alt(&images),
+                ]);
+            }
+        }
+
+        table
+    }
+
+    fn authors(&self) -> Table {
+        #[derive(Default)]
+        struct Row<'g> {
+            standalone: bool,
+            roles: Vec<&'g str>,
+            games: Vec<&'g str>,
+        }
+
+        let mut authors: BTreeMap<&str, Row> = BTreeMap::new();
+        for game in self.games.iter() {
+            for author in game.authors.iter() {
+                let row = authors.entry(author.name.as_str()).or_default();
+                row.standalone |= author.standalone;
+                row.roles.extend(author.roles.iter().map(|r| r.as_str()));
+                row.games.push(&game.id);
+            }
+        }
+
+        let header = ["name", "standalone", "roles", "games"];
+        let mut table = vec![header.map(String::from).to_vec()];
+
+        for (name, mut row) in authors {
+            row.roles.sort_unstable();
+            row.roles.dedup();
+            table.push(vec![
+                name.to_owned(),
+                row.standalone.to_string(),
+                self.join(row.roles),
+                self.join(row.games),
+            ]);
+        }
+
+        table
+    }
+
+    fn tags(&self) -> Table {
+        let tags = self.data.facets_index(self.profile.include_unlisted);
+
+        let header = ["namespace", "value", "label", "count", "games"];
+        let mut table = vec![header.map(String::from).to_vec()];
+
+        for ((namespace, value), games) in tags {
+            let label = self.data.facet_label(LangId::default(), &namespace, &value);
+            table.push(vec![
+                namespace,
+                value,
+                label,
+                games.len().to_string(),
+                self.join(games.iter().map(|g| &g.id)),
+            ]);
+        }
+
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use libfrt::testing::Fixture;
+
+    use crate::BackendCSV;
+
+    #[test]
+    fn test_games_tsv() {
+        let mut fixture = Fixture::new("csv");
+        let bar = fixture.data.games.get_mut("bar-game").unwrap();
+        Rc::get_mut(bar).unwrap().name = "Bar\tGame".to_owned();
+
+        let profile = "format = \"tsv\"\nlist_separator = \" | \"";
+        let mut backend = BackendCSV::new(Some(toml::from_str(profile).unwrap())).unwrap();
+        fixture.render(&mut backend);
+
+        let games = std::fs::read_to_string(fixture.output().join("games.tsv")).unwrap();
+        let rows: Vec<Vec<&str>> = games.lines().map(|l| l.split('\t').collect()).collect();
+
+        assert_eq!(
+            rows[0],
+            [
+                "id",
+                "state",
+                "name",
+                "name.zh-cn",
+                "tags",
+                "links",
+                "screenshots",
+                "translated.zh-cn",
+                "added",
+                "updated",
+            ]
+        );
+
+        // Values containing the delimiter are quoted
+        assert!(games.contains("bar-game\tactive\t\"Bar\tGame\"\t\t"));
+
+        let foo = rows.iter().find(|r| r[0] == "foo-game").unwrap();
+        assert_eq!(foo[3], "富游戏");
+        assert_eq!(
+            foo[4],
+            "language:en | monetization:free | platform:linux | platform:windows | playable:en"
+        );
+        assert_eq!(foo[7], "true");
+    }
+
+    #[test]
+    fn test_exclude_sensitive() {
+        let mut fixture = Fixture::new("csv-sfw");
+        fixture.data.exclude_sensitive();
+        fixture.render(&mut BackendCSV::new(None).unwrap());
+
+        fixture.assert_sensitive_excluded();
+    }
+}
@@ -0,0 +1,100 @@
+/// Builder of a gemtext document
+#[derive(Default)]
+pub struct Gemtext {
+    out: String,
+}
+
+/// Whether a line of text would be read as a gemtext line type
+fn is_markup(line: &str) -> bool {
+    ["=>", "#", "*", ">", "```"]
+        .iter()
+        .any(|p| line.starts_with(p))
+}
+
+fn single_line(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl Gemtext {
+    pub fn heading(&mut self, level: usize, text: &str) -> &mut Self {
+        let level = level.clamp(1, 3);
+        self.out.push_str(&"#".repeat(level));
+        self.out.push(' ');
+        self.out.push_str(&single_line(text));
+        self.out.push('\n');
+        self
+    }
+
+    /// Paragraphs of plain text, lines which look like markup are escaped
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        for line in text.lines() {
+            if is_markup(line) {
+                self.out.push(' ');
+            }
+            self.out.push_str(line.trim_end());
+            self.out.push('\n');
+        }
+        self
+    }
+
+    pub fn item(&mut self, text: &str) -> &mut Self {
+        self.out.push_str("* ");
+        self.out.push_str(&single_line(text));
+        self.out.push('\n');
+        self
+    }
+
+    pub fn link(&mut self, uri: &str, label: &str) -> &mut Self {
+        self.out.push_str("=> ");
+        self.out.push_str(&uri.replace(' ', "%20"));
+        let label = single_line(label);
+        if !label.is_empty() {
+            self.out.push(' ');
+            self.out.push_str(&label);
+        }
+        self.out.push('\n');
+        self
+    }
+
+    pub fn quote(&mut self, text: &str) -> &mut Self {
+        self.out.push_str("> ");
+        self.out.push_str(&single_line(text));
+        self.out.push('\n');
+        self
+    }
+
+    pub fn blank(&mut self) -> &mut Self {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+        self
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Gemtext;
+
+    #[test]
+    fn test_text_escape() {
+        let mut g = Gemtext::default();
+        g.text("# Title\n* item\n=> link\n```\nplain #1  \n> quote");
+        assert_eq!(
+            g.finish(),
+            " # Title\n * item\n => link\n ```\nplain #1\n > quote\n"
+        );
+    }
+
+    #[test]
+    fn test_single_line() {
+        let mut g = Gemtext::default();
+        g.heading(5, "Two\nlines")
+            .link("a b.gmi", "")
+            .item("  x  y ");
+        assert_eq!(g.finish(), "### Two lines\n=> a%20b.gmi\n* x y\n");
+    }
+}
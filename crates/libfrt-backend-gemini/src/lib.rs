@@ -0,0 +1,126 @@
+mod gemtext;
+mod pages;
+mod profile;
+
+#[macro_use]
+extern crate log;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use libfrt::backend::registry::BackendRegistration;
+use libfrt::backend::{clean_output_dir, copy_bundled_files, Backend, BackendArguments};
+use libfrt::i18n::LangId;
+use libfrt::profile::Profile;
+use libfrt::utils::fs::{copy_dir, ensure_dir};
+use libfrt::ContextData;
+use pages::PageContext;
+use profile::ProfileGemini;
+
+/// Registration of the gemini backend
+pub fn registration() -> BackendRegistration {
+    BackendRegistration {
+        name: "gemini",
+        description: "Gemini capsule of gemtext pages",
+        constructor: Some(|value| Ok(Box::new(BackendGemini::new(value)?))),
+        config: profile::CONFIG_KEYS,
+    }
+}
+
+/// Writes a gemini capsule with the same layout for every language:
+/// `<lang>/index.gmi`, lists, `<lang>/tags/` and `<lang>/games/<id>.gmi`.
+/// Bundled files are copied to the same `assets/` paths as the WWW backend.
+pub struct BackendGemini {
+    pub profile: ProfileGemini,
+
+    output: Option<PathBuf>,
+    langs: Vec<LangId>,
+}
+
+impl BackendGemini {
+    pub fn new(value: Option<toml::Value>) -> Result<Self> {
+        Ok(Self {
+            profile: match value {
+                Some(value) => ProfileGemini::from_value(value)?,
+                None => ProfileGemini::default(),
+            },
+
+            output: None,
+            langs: Vec::new(),
+        })
+    }
+}
+
+impl Backend for BackendGemini {
+    fn resync(
+        &mut self,
+        _profile: &Profile,
+        data: &mut ContextData,
+        args: &BackendArguments,
+    ) -> Result<()> {
+        self.output = clean_output_dir(args)?;
+        self.langs = data.langs();
+
+        Ok(())
+    }
+
+    fn render(&self, _profile: &Profile, data: &ContextData) -> Result<BackendArguments> {
+        let Some(output_dir) = &self.output else {
+            libfrt::bail!(InvalidArgument, "Gemini backend requires file-system output")
+        };
+
+        let mut files = vec![(
+            String::from("index.gmi"),
+            pages::root_index(data, &self.langs),
+        )];
+
+        for lang in self.langs.iter() {
+            info!("Render starting, lang: {}", lang.as_str());
+
+            let pc = PageContext {
+                data,
+                profile: &self.profile,
+                lang: *lang,
+            };
+
+            for (path, contents) in pc.render()? {
+                files.push((format!("{}/{}", lang.as_str(), path), contents));
+            }
+        }
+
+        for src in self.profile.path_static_layers.iter() {
+            info!("Copy static layer '{}'", src);
+            copy_dir(src, output_dir)?;
+        }
+
+        info!("Write {} gemtext files", files.len());
+        for (path, contents) in files.iter() {
+            let f = output_dir.join(path);
+            ensure_dir(&f)?;
+            std::fs::write(f, contents)?;
+        }
+
+        info!("Copy bundled files");
+        copy_bundled_files(data, output_dir)?;
+
+        Ok(BackendArguments::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libfrt::testing::Fixture;
+
+    use super::BackendGemini;
+
+    #[test]
+    fn test_exclude_sensitive() {
+        let mut fixture = Fixture::new("gemini-sfw");
+        fixture.data.exclude_sensitive();
+        fixture.render(&mut BackendGemini::new(None).unwrap());
+
+        assert!(fixture.output().join("zh-cn/games/bar-game.gmi").is_file());
+        fixture.assert_sensitive_excluded();
+    }
+}
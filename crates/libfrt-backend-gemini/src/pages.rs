@@ -0,0 +1,411 @@
+use anyhow::Result;
+
+use crate::gemtext::Gemtext;
+use crate::profile::ProfileGemini;
+use libfrt::backend::relative_root as root;
+use libfrt::entries::game::{Description, Game, GameState};
+use libfrt::entries::link::Link;
+use libfrt::entries::media::{Image, Media};
+use libfrt::i18n::LangId;
+use libfrt::utils::collate::CollationKey;
+use libfrt::ContextData;
+
+/// Renders the pages of a single language
+pub struct PageContext<'a> {
+    pub data: &'a ContextData,
+    pub profile: &'a ProfileGemini,
+    pub lang: LangId,
+}
+
+impl PageContext<'_> {
+    fn ui(&self, k: &str) -> &str {
+        self.data.ui(self.lang, k)
+    }
+
+    fn name<'g>(&self, game: &'g Game) -> &'g str {
+        game.localized_name(self.lang)
+    }
+
+    fn description<'g>(&self, game: &'g Game) -> &'g str {
+        match game.localized_description(self.lang) {
+            Description::Plain(s) | Description::Markdown(s) => s,
+        }
+    }
+
+    fn game_uri(&self, depth: usize, id: &str) -> String {
+        format!("{}/{}/games/{}.gmi", root(depth), self.lang.as_str(), id)
+    }
+
+    /// Sort games by localized name
+    fn sort(&self, games: &mut [&Game]) {
+        games.sort_by_cached_key(|g| CollationKey::new(self.name(g), self.lang));
+    }
+
+    /// Listed games, sorted by localized name
+    fn listed(&self) -> Vec<&Game> {
+        let mut games: Vec<&Game> = self
+            .data
+            .games
+            .values()
+            .filter(|g| g.state.is_listed())
+            .map(|g| g.as_ref())
+            .collect();
+        self.sort(&mut games);
+        games
+    }
+
+    /// All pages of the language, with paths relative to the language directory
+    pub fn render(&self) -> Result<Vec<(String, String)>> {
+        let listed = self.listed();
+        let mut pages = vec![
+            (String::from("index.gmi"), self.index()),
+            (
+                String::from("list.gmi"),
+                self.list(self.ui("list.by_name"), &listed),
+            ),
+        ];
+
+        let mut recent = listed.clone();
+        recent.sort_by_key(|g| std::cmp::Reverse(g.updated));
+        pages.push((
+            String::from("list-updated.gmi"),
+            self.list(self.ui("list.by_updated"), &recent),
+        ));
+
+        pages.extend(self.tags());
+
+        let mut games: Vec<_> = self.data.games.values().collect();
+        games.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        for game in games {
+            pages.push((format!("games/{}.gmi", game.id), self.game(game)?));
+        }
+
+        Ok(pages)
+    }
+
+    fn index(&self) -> String {
+        let mut g = Gemtext::default();
+        g.heading(1, self.ui("main.title"))
+            .text(self.ui("main.description"))
+            .blank()
+            .link("list.gmi", self.ui("nav.list"))
+            .link("list-updated.gmi", self.ui("list.by_updated"))
+            .link("tags/index.gmi", self.ui("nav.tags"))
+            .blank()
+            .link("../index.gmi", self.ui("nav.languages"));
+        g.finish()
+    }
+
+    fn list(&self, title: &str, games: &[&Game]) -> String {
+        let mut g = Gemtext::default();
+        g.heading(1, title).blank();
+
+        for game in games {
+            g.link(&self.game_uri(1, &game.id), self.name(game));
+        }
+
+        g.blank().link("index.gmi", self.ui("nav.index"));
+        g.finish()
+    }
+
+    fn tags(&self) -> Vec<(String, String)> {
+        let mut tags = self.data.facets_index(false);
+        for games in tags.values_mut() {
+            self.sort(games);
+        }
+
+        let mut pages = Vec::new();
+
+        let mut index = Gemtext::default();
+        index.heading(1, self.ui("nav.tags"));
+
+        let mut last_namespace = "";
+        for ((namespace, value), games) in tags.iter() {
+            if namespace != last_namespace {
+                index
+                    .blank()
+                    .heading(2, self.ui(&format!("facet_namespaces.{}", namespace)));
+                last_namespace = namespace;
+            }
+
+            let label = self.data.facet_label(self.lang, namespace, value);
+            index.link(
+                &format!("{}/{}.gmi", namespace, value),
+                &format!("{} ({})", label, games.len()),
+            );
+
+            let mut page = Gemtext::default();
+            page.heading(1, &label).blank();
+            for game in games {
+                page.link(&self.game_uri(3, &game.id), self.name(game));
+            }
+            page.blank().link("../index.gmi", self.ui("nav.tags"));
+
+            pages.push((format!("tags/{}/{}.gmi", namespace, value), page.finish()));
+        }
+
+        index.blank().link("../index.gmi", self.ui("nav.index"));
+        pages.push((String::from("tags/index.gmi"), index.finish()));
+
+        pages
+    }
+
+    fn link(&self, g: &mut Gemtext, link: &Link) {
+        let mut label = link.localized_label(self.lang).to_owned();
+
+        if let Some(patch) = &link.patch {
+            label.push_str(&format!(
+                " ({})",
+                self.data.facet_label(self.lang, "language", &patch.lang)
+            ));
+            if let Some(author) = &patch.author {
+                label.push_str(&format!(" {} {}", self.ui("game.patch_author"), author));
+            }
+            if let Some(completeness) = patch.completeness {
+                label.push_str(&format!(
+                    ", {} {}%",
+                    self.ui("game.patch_completeness"),
+                    completeness
+                ));
+            }
+        }
+
+        g.link(&link.uri, &label);
+    }
+
+    fn game(&self, game: &Game) -> Result<String> {
+        let mut g = Gemtext::default();
+        g.heading(1, self.name(game));
+
+        match &game.state {
+            GameState::Expunged { .. } => {
+                g.blank().text(self.ui("tombstone.expunged"));
+                return Ok(self.footer(g));
+            }
+            GameState::Redirected { target } => {
+                let name = self.data.game_name(target, self.lang);
+                g.blank()
+                    .text(self.ui("tombstone.redirected"))
+                    .link(&self.game_uri(2, target), name);
+                return Ok(self.footer(g));
+            }
+            GameState::Delisted { .. } => {
+                g.quote(self.ui("warnbox.delisted"));
+            }
+            GameState::Active => {}
+        }
+
+        if let Some(thumbnail) = &game.thumbnail {
+            g.link(&game.image_uri(&root(2), thumbnail)?, self.name(game));
+        }
+
+        if let Some(brief) = game.localized_brief_description(self.lang) {
+            g.blank().quote(brief);
+        }
+
+        g.blank().text(self.description(game)).blank();
+
+        let facet_list = |namespace: &str, values: Vec<&str>| {
+            values
+                .iter()
+                .map(|v| self.data.facet_label(self.lang, namespace, v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        if !game.platforms.is_empty() {
+            let platforms = game.platforms.iter().map(|p| p.as_str()).collect();
+            g.item(&format!(
+                "{}: {}",
+                self.ui("game.platforms"),
+                facet_list("platform", platforms)
+            ));
+        }
+        if let Some(status) = &game.release_status {
+            g.item(&format!(
+                "{}: {}",
+                self.ui("game.release_status"),
+                facet_list("release-status", vec![status.as_str()])
+            ));
+        }
+        if let Some(date) = &game.release_date {
+            g.item(&format!("{}: {}", self.ui("game.release_date"), date));
+        }
+        if let Some(monetization) = &game.monetization {
+            g.item(&format!(
+                "{}: {}",
+                self.ui("game.monetization"),
+                facet_list("monetization", vec![monetization.as_str()])
+            ));
+        }
+        if !game.languages.is_empty() {
+            let languages = game.languages.iter().map(|l| l.as_str()).collect();
+            g.item(&format!(
+                "{}: {}",
+                self.ui("game.languages"),
+                facet_list("language", languages)
+            ));
+        }
+
+        let mut last_category = None;
+        for link in Link::displayed(&game.links, self.lang) {
+            if last_category != Some(link.category()) {
+                g.blank().heading(
+                    2,
+                    self.ui(&format!("link_categories.{}", link.category().as_str())),
+                );
+                last_category = Some(link.category());
+            }
+            self.link(&mut g, link);
+        }
+
+        let medias: Vec<_> = game
+            .medias
+            .iter()
+            .filter(|m| !(m.sensitive && self.profile.hide_sensitive_media))
+            .collect();
+
+        if !medias.is_empty() {
+            g.blank().heading(2, self.ui("game.screenshots"));
+        }
+
+        for gm in medias {
+            let prefix = match gm.sensitive {
+                true => format!("{} ", self.ui("sensitive.label")),
+                false => String::new(),
+            };
+
+            let image_label = |image: &Image| {
+                let text = image
+                    .alt
+                    .get(self.lang)
+                    .or(image.caption.get(self.lang))
+                    .unwrap_or(self.ui("media.image"));
+                format!("{}{}", prefix, text)
+            };
+
+            match &gm.media {
+                Media::Image(image) => {
+                    g.link(&game.image_uri(&root(2), image)?, &image_label(image));
+                }
+                Media::HBox(images) => {
+                    for image in images {
+                        g.link(&game.image_uri(&root(2), image)?, &image_label(image));
+                    }
+                }
+                Media::Youtube(id) => {
+                    g.link(
+                        &format!("https://www.youtube.com/watch?v={}", id),
+                        &format!("{}{}", prefix, self.ui("media.youtube")),
+                    );
+                }
+                Media::Video { sources, .. } => {
+                    for source in sources {
+                        g.link(
+                            &game.file_uri(&root(2), &source.uri, source.remote),
+                            &format!("{}{} ({})", prefix, self.ui("media.video"), source.mime),
+                        );
+                    }
+                }
+            }
+        }
+
+        if !game.relations.is_empty() {
+            g.blank().heading(2, self.ui("game.related"));
+        }
+
+        for relation in game.relations.iter() {
+            let name = self.data.game_name(&relation.target, self.lang);
+            g.link(
+                &self.game_uri(2, &relation.target),
+                &format!(
+                    "{}: {}",
+                    self.ui(&format!("relations.{}", relation.kind.as_str())),
+                    name
+                ),
+            );
+        }
+
+        Ok(self.footer(g))
+    }
+
+    fn footer(&self, mut g: Gemtext) -> String {
+        g.blank()
+            .link("../list.gmi", self.ui("nav.list"))
+            .link("../index.gmi", self.ui("nav.index"));
+        g.finish()
+    }
+}
+
+/// The capsule root, linking to the index of every language
+pub fn root_index(data: &ContextData, langs: &[LangId]) -> String {
+    let mut g = Gemtext::default();
+    g.heading(1, data.ui(LangId::default(), "main.title")).blank();
+
+    for lang in langs {
+        g.link(
+            &format!("{}/index.gmi", lang.as_str()),
+            &data.facet_label(*lang, "language", &lang.as_str()),
+        );
+    }
+
+    g.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use libfrt::i18n::LangId;
+    use libfrt::testing::Fixture;
+
+    use super::PageContext;
+    use crate::profile::ProfileGemini;
+
+    fn pages(fixture: &Fixture, lang: &str) -> Vec<(String, String)> {
+        let pc = PageContext {
+            data: &fixture.data,
+            profile: &ProfileGemini::default(),
+            lang: LangId::from_tag(lang).unwrap(),
+        };
+        pc.render().unwrap()
+    }
+
+    fn page<'p>(pages: &'p [(String, String)], path: &str) -> &'p str {
+        &pages.iter().find(|(p, _)| p == path).unwrap().1
+    }
+
+    #[test]
+    fn test_game_links() {
+        let fixture = Fixture::new("gemini");
+        let pages = pages(&fixture, "zh-cn");
+
+        let foo = page(&pages, "games/foo-game.gmi");
+        assert!(foo.contains("=> ../../assets/game/foo-game/thumbnail.png 富游戏\n"));
+        assert!(foo.contains("=> ../../assets/game/foo-game/screenshot.png A fox"));
+        assert!(foo.contains("=> https://images.example.com/foo/2.png Map\n"));
+        assert!(foo.contains("\n # Foo\n"));
+        assert!(foo.contains("=> ../../zh-cn/games/bar-game.gmi 续作: Bar Game\n"));
+        assert!(foo.contains("\n=> ../list.gmi\n=> ../index.gmi\n"));
+
+        let old = page(&pages, "games/old-foo.gmi");
+        assert!(old.contains("=> ../../zh-cn/games/foo-game.gmi 富游戏\n"));
+
+        let en = self::pages(&fixture, "en");
+        let foo = page(&en, "games/foo-game.gmi");
+        assert!(foo.contains("=> ../../en/games/bar-game.gmi Sequel: Bar Game\n"));
+    }
+
+    #[test]
+    fn test_tag_links() {
+        let fixture = Fixture::new("gemini-tags");
+        let pages = pages(&fixture, "en");
+
+        let linux = page(&pages, "tags/platform/linux.gmi");
+        assert!(linux.starts_with("# Linux\n"));
+        assert!(linux.contains("=> ../../../en/games/foo-game.gmi Foo Game\n"));
+        assert!(!linux.contains("hidden-game"));
+
+        let index = page(&pages, "tags/index.gmi");
+        assert!(index.contains("=> platform/linux.gmi Linux (1)\n"));
+        assert!(index.contains("=> platform/windows.gmi Windows (2)\n"));
+    }
+}
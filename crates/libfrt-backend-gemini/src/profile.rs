@@ -0,0 +1,33 @@
+use anyhow::Result;
+use libfrt::backend::registry::BackendConfigKey;
+use serde::Deserialize;
+
+/// Keys of `ProfileGemini`, for `frt backends`
+pub const CONFIG_KEYS: &[BackendConfigKey] = &[
+    BackendConfigKey {
+        key: "path_static_layers",
+        kind: "list of paths",
+        default: "[]",
+        description: "Directories copied to the output as is, in order",
+    },
+    BackendConfigKey {
+        key: "hide_sensitive_media",
+        kind: "bool",
+        default: "false",
+        description: "Leave out sensitive medias, instead of marking them",
+    },
+];
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ProfileGemini {
+    pub path_static_layers: Vec<String>,
+
+    pub hide_sensitive_media: bool,
+}
+
+impl ProfileGemini {
+    pub fn from_value(value: toml::Value) -> Result<Self> {
+        Ok(ProfileGemini::deserialize(value)?)
+    }
+}
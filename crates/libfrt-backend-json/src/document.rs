@@ -0,0 +1,385 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use libfrt::entries::game::{Description, Game, GameState};
+use libfrt::entries::link::{Link, LocalizationPatch};
+use libfrt::entries::media::{Image, Media};
+use libfrt::i18n::{L10nText, LangId};
+
+/// Version of the document format, also the top-level directory of the API
+pub const API_VERSION: u32 = 1;
+
+/// Turns bundle files into the URLs the WWW backend copies them to
+pub struct AssetResolver<'a> {
+    pub prefix: &'a str,
+}
+
+impl AssetResolver<'_> {
+    fn file(&self, game: &Game, name: &str, remote: bool) -> String {
+        game.file_uri(self.prefix, name, remote)
+    }
+
+    fn image(&self, game: &Game, image: &Image) -> Result<DocImage> {
+        Ok(DocImage {
+            uri: game.image_uri(self.prefix, image)?,
+            width: image.size.map(|s| s.0),
+            height: image.size.map(|s| s.1),
+            alt: l10n_text(&image.alt),
+            caption: l10n_text(&image.caption),
+        })
+    }
+}
+
+fn l10n_map(map: &HashMap<LangId, String>) -> BTreeMap<String, String> {
+    map.iter().map(|(k, v)| (k.as_str(), v.clone())).collect()
+}
+
+fn l10n_text(text: &L10nText) -> BTreeMap<String, String> {
+    l10n_map(&text.0)
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocDescription<'a> {
+    pub format: &'static str,
+    pub text: &'a str,
+}
+
+impl<'a> DocDescription<'a> {
+    fn new(description: &'a Description) -> Self {
+        match description {
+            Description::Plain(text) => Self {
+                format: "plain",
+                text,
+            },
+            Description::Markdown(text) => Self {
+                format: "markdown",
+                text,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocImage {
+    pub uri: String,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub alt: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub caption: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocVideoSource {
+    pub mime: String,
+    pub uri: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocCaption {
+    pub lang: String,
+    pub label: Option<String>,
+    pub uri: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum DocMediaKind {
+    Image {
+        image: DocImage,
+    },
+    Youtube {
+        id: String,
+    },
+    Video {
+        sources: Vec<DocVideoSource>,
+        poster: Option<DocImage>,
+        captions: Vec<DocCaption>,
+    },
+    Hbox {
+        images: Vec<DocImage>,
+    },
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocMedia {
+    pub sensitive: bool,
+    #[serde(flatten)]
+    pub kind: DocMediaKind,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocLink<'a> {
+    /// Name of the stock link rule, `None` for custom links
+    pub rule: Option<&'a str>,
+    pub category: &'static str,
+    pub icon: Option<&'a str>,
+    pub label: BTreeMap<String, String>,
+    /// Final URL, after applying the stock link rule
+    pub href: &'a str,
+    /// Languages the link is relevant to, empty for all languages
+    pub langs: Vec<String>,
+    pub patch: Option<&'a LocalizationPatch>,
+}
+
+impl<'a> DocLink<'a> {
+    fn new(link: &'a Link) -> Self {
+        Self {
+            rule: link.rule.as_ref().map(|r| r.name.as_str()),
+            category: link.category().as_str(),
+            icon: link.rule.as_ref().map(|r| r.icon.as_str()),
+            label: l10n_map(&link.label),
+            href: &link.uri,
+            langs: link
+                .rule
+                .iter()
+                .flat_map(|r| r.langs.iter())
+                .map(|l| l.as_str())
+                .collect(),
+            patch: link.patch.as_ref(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocAuthor<'a> {
+    pub name: &'a str,
+    pub roles: &'a [String],
+    pub standalone: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocRelation<'a> {
+    pub kind: &'static str,
+    pub target: &'a str,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocGameL10n<'a> {
+    pub name: Option<&'a str>,
+    pub description: Option<DocDescription<'a>>,
+    pub brief_description: Option<&'a str>,
+}
+
+/// Document of a single game, `games/<id>.json`
+#[derive(Serialize, Debug)]
+pub struct DocGame<'a> {
+    pub version: u32,
+    pub id: &'a str,
+    pub state: &'static str,
+    pub state_reason: Option<&'a str>,
+    pub redirect_to: Option<&'a str>,
+
+    pub name: &'a str,
+    pub description: DocDescription<'a>,
+    pub brief_description: Option<&'a str>,
+    pub thumbnail: Option<DocImage>,
+    pub rating: &'static str,
+
+    pub platforms: Vec<&'static str>,
+    pub release_status: Option<&'static str>,
+    pub release_date: Option<String>,
+    pub languages: &'a [String],
+    pub playable_languages: Vec<&'a str>,
+    pub monetization: Option<&'static str>,
+    pub tags: Vec<String>,
+
+    pub authors: Vec<DocAuthor<'a>>,
+    pub relations: Vec<DocRelation<'a>>,
+    pub links: Vec<DocLink<'a>>,
+    pub medias: Vec<DocMedia>,
+
+    pub l10n: BTreeMap<String, DocGameL10n<'a>>,
+
+    pub added: Option<i64>,
+    pub updated: Option<i64>,
+}
+
+impl<'a> DocGame<'a> {
+    pub fn new(game: &'a Game, assets: &AssetResolver) -> Result<Self> {
+        let (state_reason, redirect_to) = match &game.state {
+            GameState::Active => (None, None),
+            GameState::Delisted { reason } | GameState::Expunged { reason } => {
+                (reason.as_deref(), None)
+            }
+            GameState::Redirected { target } => (None, Some(target.as_str())),
+        };
+
+        let mut medias = Vec::new();
+        for gm in game.medias.iter() {
+            let kind = match &gm.media {
+                Media::Image(image) => DocMediaKind::Image {
+                    image: assets.image(game, image)?,
+                },
+                Media::Youtube(id) => DocMediaKind::Youtube { id: id.clone() },
+                Media::Video {
+                    sources,
+                    poster,
+                    captions,
+                } => DocMediaKind::Video {
+                    sources: sources
+                        .iter()
+                        .map(|s| DocVideoSource {
+                            mime: s.mime.clone(),
+                            uri: assets.file(game, &s.uri, s.remote),
+                        })
+                        .collect(),
+                    poster: match poster {
+                        Some(poster) => Some(assets.image(game, poster)?),
+                        None => None,
+                    },
+                    captions: captions
+                        .iter()
+                        .map(|c| DocCaption {
+                            lang: c.lang.clone(),
+                            label: c.label.clone(),
+                            uri: assets.file(game, &c.uri, c.remote),
+                        })
+                        .collect(),
+                },
+                Media::HBox(images) => DocMediaKind::Hbox {
+                    images: images
+                        .iter()
+                        .map(|i| assets.image(game, i))
+                        .collect::<Result<_>>()?,
+                },
+            };
+
+            medias.push(DocMedia {
+                sensitive: gm.sensitive,
+                kind,
+            });
+        }
+
+        Ok(Self {
+            version: API_VERSION,
+            id: &game.id,
+            state: game.state.as_str(),
+            state_reason,
+            redirect_to,
+
+            name: &game.name,
+            description: DocDescription::new(&game.description),
+            brief_description: game.brief_description.as_deref(),
+            thumbnail: match &game.thumbnail {
+                Some(thumbnail) => Some(assets.image(game, thumbnail)?),
+                None => None,
+            },
+            rating: game.rating.as_str(),
+
+            platforms: game.platforms.iter().map(|p| p.as_str()).collect(),
+            release_status: game.release_status.map(|s| s.as_str()),
+            release_date: game.release_date.map(|d| d.to_string()),
+            languages: &game.languages,
+            playable_languages: game.playable_languages(),
+            monetization: game.monetization.map(|m| m.as_str()),
+            tags: game.facets(),
+
+            authors: game
+                .authors
+                .iter()
+                .map(|a| DocAuthor {
+                    name: &a.name,
+                    roles: &a.roles,
+                    standalone: a.standalone,
+                })
+                .collect(),
+            relations: game
+                .relations
+                .iter()
+                .map(|r| DocRelation {
+                    kind: r.kind.as_str(),
+                    target: &r.target,
+                })
+                .collect(),
+            links: game.links.iter().map(DocLink::new).collect(),
+            medias,
+
+            l10n: game
+                .l10n
+                .iter()
+                .map(|(lang, l10n)| {
+                    (
+                        lang.as_str(),
+                        DocGameL10n {
+                            name: l10n.name.as_deref(),
+                            description: l10n.description.as_ref().map(DocDescription::new),
+                            brief_description: l10n.brief_description.as_deref(),
+                        },
+                    )
+                })
+                .collect(),
+
+            added: game.added,
+            updated: game.updated,
+        })
+    }
+}
+
+/// Summary of a listed game in `index.json`
+#[derive(Serialize, Debug)]
+pub struct DocIndexEntry<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub brief_description: Option<&'a str>,
+    pub thumbnail: Option<&'a DocImage>,
+    pub rating: &'static str,
+    pub tags: &'a [String],
+    pub added: Option<i64>,
+    pub updated: Option<i64>,
+    /// Path of the game document, relative to the API root
+    pub href: String,
+}
+
+impl<'a> DocIndexEntry<'a> {
+    pub fn new(doc: &'a DocGame) -> Self {
+        Self {
+            id: doc.id,
+            name: doc.name,
+            brief_description: doc.brief_description,
+            thumbnail: doc.thumbnail.as_ref(),
+            rating: doc.rating,
+            tags: &doc.tags,
+            added: doc.added,
+            updated: doc.updated,
+            href: format!("games/{}.json", doc.id),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocIndex<'a> {
+    pub version: u32,
+    pub games: Vec<DocIndexEntry<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DocAuthorCredit<'a> {
+    pub game: &'a str,
+    pub roles: &'a [String],
+}
+
+/// All authors credited by listed games, `authors.json`
+#[derive(Serialize, Debug)]
+pub struct DocAuthors<'a> {
+    pub version: u32,
+    pub authors: BTreeMap<&'a str, Vec<DocAuthorCredit<'a>>>,
+}
+
+/// Listed games of each tag, `tags.json`
+#[derive(Serialize, Debug)]
+pub struct DocTags<'a> {
+    pub version: u32,
+    pub tags: BTreeMap<&'a str, Vec<&'a str>>,
+}
+
+/// Ui strings of a language, `l10n/<lang>.json`
+#[derive(Serialize, Debug)]
+pub struct DocL10n<'a> {
+    pub version: u32,
+    pub lang: String,
+    pub ui: &'a toml::Value,
+}
@@ -0,0 +1,293 @@
+mod document;
+mod profile;
+
+#[macro_use]
+extern crate log;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use document::{
+    AssetResolver, DocAuthorCredit, DocAuthors, DocGame, DocIndex, DocIndexEntry, DocL10n,
+    DocTags, API_VERSION,
+};
+use libfrt::backend::registry::BackendRegistration;
+use libfrt::backend::{output_dir, Backend, BackendArguments};
+use libfrt::profile::Profile;
+use libfrt::utils::fs::ensure_dir;
+use libfrt::ContextData;
+use profile::ProfileJSON;
+
+/// JSON schema of all documents
+const SCHEMA: &str = include_str!("schema.json");
+
+/// Registration of the JSON backend
+pub fn registration() -> BackendRegistration {
+    BackendRegistration {
+        name: "json",
+        description: "Static, versioned JSON API",
+        constructor: Some(|value| Ok(Box::new(BackendJSON::new(value)?))),
+        config: profile::CONFIG_KEYS,
+    }
+}
+
+/// Writes a static, versioned JSON API:
+///
+/// - `v1/index.json`: listed games
+/// - `v1/games/<id>.json`: a single game, with resolved links
+/// - `v1/authors.json`, `v1/tags.json`: listed games by author and tag
+/// - `v1/l10n/<lang>.json`: ui strings of each language
+/// - `v1/schema.json`: JSON schema of the documents above
+pub struct BackendJSON {
+    pub profile: ProfileJSON,
+
+    output: Option<PathBuf>,
+}
+
+impl BackendJSON {
+    pub fn new(value: Option<toml::Value>) -> Result<Self> {
+        Ok(Self {
+            profile: match value {
+                Some(value) => ProfileJSON::from_value(value)?,
+                None => ProfileJSON::default(),
+            },
+
+            output: None,
+        })
+    }
+
+    fn write<T: Serialize>(&self, dir: &Path, name: &str, doc: &T) -> Result<()> {
+        let contents = match self.profile.pretty {
+            true => serde_json::to_string_pretty(doc)?,
+            false => serde_json::to_string(doc)?,
+        };
+
+        let f = dir.join(name);
+        ensure_dir(&f)?;
+        std::fs::write(f, contents)?;
+
+        Ok(())
+    }
+}
+
+impl Backend for BackendJSON {
+    fn resync(
+        &mut self,
+        _profile: &Profile,
+        _data: &mut ContextData,
+        args: &BackendArguments,
+    ) -> Result<()> {
+        if let Some(output_dir) = output_dir(args)? {
+            let api_dir = output_dir.join(format!("v{}", API_VERSION));
+            if std::fs::metadata(&api_dir).is_ok() {
+                std::fs::remove_dir_all(&api_dir)?;
+            }
+
+            self.output = Some(api_dir);
+        }
+
+        Ok(())
+    }
+
+    fn render(&self, profile: &Profile, data: &ContextData) -> Result<BackendArguments> {
+        let Some(dir) = &self.output else {
+            libfrt::bail!(InvalidArgument, "JSON backend requires file-system output")
+        };
+
+        let assets = AssetResolver {
+            prefix: &profile.authority_prefix,
+        };
+
+        let games = data.exported_games(self.profile.include_unlisted);
+
+        let docs = games
+            .iter()
+            .map(|g| DocGame::new(g, &assets))
+            .collect::<Result<Vec<_>>>()?;
+
+        info!("Write {} game documents", docs.len());
+        for doc in docs.iter() {
+            self.write(dir, &format!("games/{}.json", doc.id), doc)?;
+        }
+
+        let listed: Vec<_> = games
+            .iter()
+            .zip(docs.iter())
+            .filter(|(g, _)| g.state.is_listed())
+            .collect();
+
+        let index = DocIndex {
+            version: API_VERSION,
+            games: listed.iter().map(|(_, d)| DocIndexEntry::new(d)).collect(),
+        };
+        self.write(dir, "index.json", &index)?;
+
+        let mut authors = DocAuthors {
+            version: API_VERSION,
+            authors: BTreeMap::new(),
+        };
+        let mut tags = DocTags {
+            version: API_VERSION,
+            tags: BTreeMap::new(),
+        };
+        for (game, doc) in listed.iter() {
+            for author in game.authors.iter() {
+                authors
+                    .authors
+                    .entry(&author.name)
+                    .or_default()
+                    .push(DocAuthorCredit {
+                        game: &game.id,
+                        roles: &author.roles,
+                    });
+            }
+
+            for tag in doc.tags.iter() {
+                tags.tags.entry(tag).or_default().push(&game.id);
+            }
+        }
+        self.write(dir, "authors.json", &authors)?;
+        self.write(dir, "tags.json", &tags)?;
+
+        for (lang, ui) in data.ui.iter() {
+            let doc = DocL10n {
+                version: API_VERSION,
+                lang: lang.as_str(),
+                ui,
+            };
+            self.write(dir, &format!("l10n/{}.json", doc.lang), &doc)?;
+        }
+
+        let f = dir.join("schema.json");
+        ensure_dir(&f)?;
+        std::fs::write(f, SCHEMA)?;
+
+        Ok(BackendArguments::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use libfrt::testing::Fixture;
+    use serde_json::Value;
+
+    use super::BackendJSON;
+
+    fn render(fixture: &mut Fixture, profile: &str) -> std::path::PathBuf {
+        let mut backend = BackendJSON::new(Some(toml::from_str(profile).unwrap())).unwrap();
+        fixture.render(&mut backend);
+
+        fixture.output().join("v1")
+    }
+
+    fn read(path: &Path) -> Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn game_ids(dir: &Path) -> Vec<String> {
+        let mut ids: Vec<_> = std::fs::read_dir(dir.join("games"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().replace(".json", ""))
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn test_render_games() {
+        let mut fixture = Fixture::new("json");
+        let dir = render(&mut fixture, "");
+
+        let index = read(&dir.join("index.json"));
+        let listed: Vec<_> = index["games"].as_array().unwrap().iter().map(|g| &g["id"]).collect();
+        assert_eq!(listed, ["adult-game", "bar-game", "foo-game"]);
+        assert_eq!(index["games"][2]["href"], "games/foo-game.json");
+
+        assert_eq!(
+            game_ids(&dir),
+            ["adult-game", "bar-game", "foo-game", "gone-game", "hidden-game", "old-foo"]
+        );
+
+        let foo = read(&dir.join("games/foo-game.json"));
+        assert_eq!(foo["links"][0]["rule"], "steam");
+        assert_eq!(foo["links"][0]["href"], "https://store.steampowered.com/app/100");
+        assert_eq!(foo["links"][1]["rule"], "website");
+        assert_eq!(foo["links"][1]["href"], "https://foo.example.com/");
+        assert_eq!(foo["links"][2]["rule"], Value::Null);
+
+        assert_eq!(foo["thumbnail"]["uri"], "/assets/game/foo-game/thumbnail.png");
+        assert_eq!(foo["thumbnail"]["width"], 1);
+        let image = &foo["medias"][0]["image"];
+        assert_eq!(image["uri"], "/assets/game/foo-game/screenshot.png");
+        assert_eq!(image["caption"]["zh-cn"], "标题画面");
+        assert_eq!(foo["medias"][2]["type"], "hbox");
+        assert_eq!(foo["medias"][2]["images"][0]["uri"], "https://images.example.com/foo/1.png");
+        assert_eq!(foo["l10n"]["zh-cn"]["name"], "富游戏");
+        assert_eq!(foo["relations"][0]["kind"], "sequel");
+
+        let hidden = read(&dir.join("games/hidden-game.json"));
+        assert_eq!(hidden["state"], "delisted");
+        assert_eq!(hidden["state_reason"], "Removed by the author");
+        assert_eq!(read(&dir.join("games/gone-game.json"))["thumbnail"], Value::Null);
+        assert_eq!(read(&dir.join("games/old-foo.json"))["redirect_to"], "foo-game");
+
+        let tags = read(&dir.join("tags.json"));
+        assert_eq!(tags["tags"]["platform:linux"], serde_json::json!(["foo-game"]));
+        let authors = read(&dir.join("authors.json"));
+        assert_eq!(authors["authors"]["Alice"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_render_listed_only() {
+        let mut fixture = Fixture::new("json-listed");
+        let dir = render(&mut fixture, "include_unlisted = false");
+
+        assert_eq!(game_ids(&dir), ["adult-game", "bar-game", "foo-game"]);
+    }
+
+    #[test]
+    fn test_exclude_sensitive() {
+        let mut fixture = Fixture::new("json-sfw");
+        fixture.data.exclude_sensitive();
+        let dir = render(&mut fixture, "");
+
+        assert_eq!(
+            game_ids(&dir),
+            ["bar-game", "foo-game", "gone-game", "hidden-game", "old-foo"]
+        );
+        fixture.assert_sensitive_excluded();
+    }
+
+    #[test]
+    fn test_schema() {
+        let mut fixture = Fixture::new("json-schema");
+        let dir = render(&mut fixture, "pretty = true");
+
+        let schema: Value = serde_json::from_str(super::SCHEMA).unwrap();
+        assert_eq!(read(&dir.join("schema.json")), schema);
+
+        let validate = |def: &str, path: &Path| {
+            let mut schema = schema.clone();
+            schema["$ref"] = Value::from(format!("#/$defs/{def}"));
+
+            let validator = jsonschema::validator_for(&schema).unwrap();
+            let doc = read(path);
+            let errors: Vec<_> = validator.iter_errors(&doc).map(|e| e.to_string()).collect();
+            assert!(errors.is_empty(), "{}: {:?}", path.display(), errors);
+        };
+
+        validate("index", &dir.join("index.json"));
+        validate("authors", &dir.join("authors.json"));
+        validate("tags", &dir.join("tags.json"));
+        for (def, sub) in [("game", "games"), ("l10n", "l10n")] {
+            for entry in std::fs::read_dir(dir.join(sub)).unwrap() {
+                validate(def, &entry.unwrap().path());
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use libfrt::backend::registry::{BackendConfigKey, INCLUDE_UNLISTED};
+use serde::Deserialize;
+
+/// Keys of `ProfileJSON`, for `frt backends`
+pub const CONFIG_KEYS: &[BackendConfigKey] = &[
+    BackendConfigKey {
+        key: "pretty",
+        kind: "bool",
+        default: "false",
+        description: "Indent the output documents",
+    },
+    INCLUDE_UNLISTED,
+];
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ProfileJSON {
+    /// Indent the output documents
+    pub pretty: bool,
+
+    /// See `ContextData::exported_games`
+    pub include_unlisted: bool,
+}
+
+impl Default for ProfileJSON {
+    fn default() -> Self {
+        Self {
+            pretty: false,
+            include_unlisted: true,
+        }
+    }
+}
+
+impl ProfileJSON {
+    pub fn from_value(value: toml::Value) -> Result<Self> {
+        Ok(ProfileJSON::deserialize(value)?)
+    }
+}
@@ -0,0 +1,263 @@
+mod markdown;
+mod pages;
+mod profile;
+
+#[macro_use]
+extern crate log;
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+
+use libfrt::backend::registry::BackendRegistration;
+use libfrt::backend::{copy_bundled_files, output_dir, Backend, BackendArguments};
+use libfrt::i18n::LangId;
+use libfrt::profile::Profile;
+use libfrt::utils::fs::ensure_dir;
+use libfrt::ContextData;
+use pages::PageContext;
+use profile::ProfileMarkdown;
+
+/// Files written by the last build, relative to the output directory
+const MANIFEST: &str = ".frt-files";
+
+/// Registration of the markdown backend
+pub fn registration() -> BackendRegistration {
+    BackendRegistration {
+        name: "markdown",
+        description: "Markdown catalog, readable in Git forges",
+        constructor: Some(|value| Ok(Box::new(BackendMarkdown::new(value)?))),
+        config: profile::CONFIG_KEYS,
+    }
+}
+
+/// Writes a Markdown catalog with the same layout for every language:
+/// `<lang>/README.md` with a table of games, `<lang>/tags/` and
+/// `<lang>/games/<id>.md`. Bundled files are copied to the same `assets/`
+/// paths as the WWW backend.
+///
+/// The output only depends on the sources, so diffs between builds stay
+/// minimal. The output directory is usually a Git repository with files of
+/// its own, so only the files listed in `MANIFEST` by the previous build
+/// are removed before writing.
+pub struct BackendMarkdown {
+    pub profile: ProfileMarkdown,
+
+    output: Option<PathBuf>,
+    langs: Vec<LangId>,
+}
+
+impl BackendMarkdown {
+    pub fn new(value: Option<toml::Value>) -> Result<Self> {
+        Ok(Self {
+            profile: match value {
+                Some(value) => ProfileMarkdown::from_value(value)?,
+                None => ProfileMarkdown::default(),
+            },
+
+            output: None,
+            langs: Vec::new(),
+        })
+    }
+}
+
+impl Backend for BackendMarkdown {
+    fn resync(
+        &mut self,
+        _profile: &Profile,
+        data: &mut ContextData,
+        args: &BackendArguments,
+    ) -> Result<()> {
+        self.output = output_dir(args)?;
+        if let Some(dir) = &self.output {
+            remove_generated(dir)?;
+        }
+
+        self.langs = data.langs();
+
+        Ok(())
+    }
+
+    fn render(&self, _profile: &Profile, data: &ContextData) -> Result<BackendArguments> {
+        let Some(output_dir) = &self.output else {
+            libfrt::bail!(InvalidArgument, "Markdown backend requires file-system output")
+        };
+
+        let mut files = vec![(
+            String::from("README.md"),
+            pages::root_index(data, &self.langs)?,
+        )];
+
+        for lang in self.langs.iter() {
+            info!("Render starting, lang: {}", lang.as_str());
+
+            let pc = PageContext {
+                data,
+                profile: &self.profile,
+                lang: *lang,
+            };
+
+            for (path, contents) in pc.render()? {
+                files.push((format!("{}/{}", lang.as_str(), path), contents));
+            }
+        }
+
+        info!("Write {} markdown files", files.len());
+        for (path, contents) in files.iter() {
+            let f = output_dir.join(path);
+            ensure_dir(&f)?;
+            std::fs::write(f, contents)?;
+        }
+
+        let mut manifest: Vec<String> = files.into_iter().map(|(path, _)| path).collect();
+
+        info!("Copy bundled files");
+        manifest.extend(copy_bundled_files(data, output_dir)?);
+
+        manifest.sort_unstable();
+        manifest.push(String::new());
+        std::fs::write(output_dir.join(MANIFEST), manifest.join("\n"))?;
+
+        Ok(BackendArguments::default())
+    }
+}
+
+/// Remove the files listed in the manifest of `dir`, and directories left
+/// empty by them
+fn remove_generated(dir: &Path) -> Result<()> {
+    let manifest = match std::fs::read_to_string(dir.join(MANIFEST)) {
+        Ok(manifest) => manifest,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+
+    for path in manifest.lines().filter(|l| !l.is_empty()) {
+        // only relative paths inside `dir`
+        let outside = Path::new(path).components().any(|c| {
+            matches!(
+                c,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        });
+        if outside {
+            libfrt::bail!(InvalidFileOrData, "Invalid path in {}: '{}'", MANIFEST, path)
+        }
+
+        let f = dir.join(path);
+        if f.is_file() {
+            std::fs::remove_file(&f)?;
+        }
+
+        // remove_dir only succeeds for empty directories
+        let mut parent = f.parent();
+        while let Some(p) = parent.filter(|p| *p != dir) {
+            if std::fs::remove_dir(p).is_err() {
+                break;
+            }
+            parent = p.parent();
+        }
+    }
+
+    std::fs::remove_file(dir.join(MANIFEST))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::Path;
+
+    use libfrt::testing::Fixture;
+
+    use super::BackendMarkdown;
+
+    fn render(fixture: &mut Fixture, output: &Path) {
+        let mut backend = BackendMarkdown::new(None).unwrap();
+        fixture.render_to(&mut backend, output);
+    }
+
+    /// Contents of every file under `dir`, by relative path
+    fn files(dir: &Path) -> BTreeMap<String, Vec<u8>> {
+        let mut ret = BTreeMap::new();
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            match path.is_dir() {
+                true => ret.extend(files(&path).into_iter().map(|(k, v)| (format!("{name}/{k}"), v))),
+                false => {
+                    ret.insert(name, std::fs::read(&path).unwrap());
+                }
+            }
+        }
+        ret
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let mut fixture = Fixture::new("markdown");
+        let (first, second) = (fixture.root.join("first"), fixture.root.join("second"));
+        render(&mut fixture, &first);
+
+        let mut games: Vec<_> = fixture.data.games.drain().collect();
+        games.reverse();
+        fixture.data.games = games.into_iter().collect::<HashMap<_, _>>();
+        let mut ui: Vec<_> = fixture.data.ui.drain().collect();
+        ui.reverse();
+        fixture.data.ui = ui.into_iter().collect::<HashMap<_, _>>();
+        render(&mut fixture, &second);
+
+        let (first, second) = (files(&first), files(&second));
+        assert!(first.contains_key("zh-cn/games/foo-game.md"));
+        assert!(first.contains_key("assets/game/foo-game/screenshot.png"));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_keep_unmanaged_files() {
+        let mut fixture = Fixture::new("markdown-keep");
+        let output = fixture.output();
+        std::fs::create_dir_all(output.join(".git")).unwrap();
+        std::fs::write(output.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::write(output.join("LICENSE"), "CC0").unwrap();
+        render(&mut fixture, &output);
+        assert!(output.join("en/games/gone-game.md").is_file());
+
+        fixture.data.games.remove("gone-game");
+        std::fs::remove_dir_all(output.join("zh-cn/tags")).unwrap();
+        render(&mut fixture, &output);
+
+        assert!(output.join(".git/HEAD").is_file());
+        assert!(output.join("LICENSE").is_file());
+        assert!(!output.join("en/games/gone-game.md").exists());
+        assert!(output.join("en/games/foo-game.md").is_file());
+        assert!(output.join("zh-cn/tags/README.md").is_file());
+    }
+
+    #[test]
+    fn test_invalid_manifest() {
+        let fixture = Fixture::new("markdown-manifest");
+        let output = fixture.output();
+        let victim = fixture.root.join("victim.md");
+        std::fs::create_dir_all(&output).unwrap();
+        std::fs::write(&victim, "").unwrap();
+
+        for path in ["../victim.md".to_owned(), victim.display().to_string()] {
+            std::fs::write(output.join(super::MANIFEST), format!("{path}\n")).unwrap();
+            let e = super::remove_generated(&output).unwrap_err();
+            assert!(e.to_string().contains("Invalid path in .frt-files"));
+            assert!(victim.is_file());
+        }
+    }
+
+    #[test]
+    fn test_exclude_sensitive() {
+        let mut fixture = Fixture::new("markdown-sfw");
+        fixture.data.exclude_sensitive();
+        let output = fixture.output();
+        render(&mut fixture, &output);
+
+        assert!(output.join("en/games/bar-game.md").is_file());
+        fixture.assert_sensitive_excluded();
+    }
+}
@@ -0,0 +1,78 @@
+/// Escape inline text, so it is not read as Markdown or HTML
+pub fn escape(s: &str) -> String {
+    let s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut ret = String::with_capacity(s.len());
+
+    // list items (`-`, `+`, `1.`), thematic breaks (`---`), setext heading
+    // underlines (`===`) and code fences (`~~~`) are read at line start only
+    let digits = s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let marker = match s[digits..].chars().next() {
+        Some('.' | ')') if digits > 0 => Some(digits),
+        Some('-' | '+' | '=' | '~') if digits == 0 => Some(0),
+        _ => None,
+    };
+
+    for (i, c) in s.char_indices() {
+        let special = matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '#' | '|' | '<' | '>' | '!'
+        );
+        if special || Some(i) == marker {
+            ret.push('\\');
+        }
+        ret.push(c);
+    }
+
+    ret
+}
+
+/// Escape plain text paragraphs, keeping line breaks
+pub fn escape_text(s: &str) -> String {
+    s.trim()
+        .split("\n\n")
+        .map(|p| {
+            p.lines()
+                .map(escape)
+                .filter(|l| !l.is_empty())
+                .collect::<Vec<_>>()
+                .join("  \n")
+        })
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Percent-encode spaces and parentheses, which would end a link target
+pub fn uri(s: &str) -> String {
+    s.replace(' ', "%20").replace('(', "%28").replace(')', "%29")
+}
+
+pub fn link(target: &str, label: &str) -> String {
+    format!("[{}]({})", escape(label), uri(target))
+}
+
+pub fn image(target: &str, alt: &str) -> String {
+    format!("![{}]({})", escape(alt), uri(target))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_escape() {
+        assert_eq!(super::escape("a *b* [c]"), r"a \*b\* \[c\]");
+        assert_eq!(super::escape("x |\n y"), r"x \| y");
+        assert_eq!(super::escape_text("a\nb\n\n\nc"), "a  \nb\n\nc");
+        assert_eq!(super::link("a b.md", "A_B"), r"[A\_B](a%20b.md)");
+
+        assert_eq!(super::escape("- a - b"), r"\- a - b");
+        assert_eq!(super::escape("+1"), r"\+1");
+        assert_eq!(super::escape("---"), r"\---");
+        assert_eq!(super::escape("==="), r"\===");
+        assert_eq!(super::escape("~~~"), r"\~~~");
+        assert_eq!(super::escape("2023. A year"), r"2023\. A year");
+        assert_eq!(super::escape("1) a"), r"1\) a");
+        assert_eq!(super::escape("1.5 and 2."), r"1\.5 and 2.");
+        assert_eq!(super::escape("a-b"), "a-b");
+        assert_eq!(super::escape_text("a\n- b\n==="), "a  \n\\- b  \n\\===");
+    }
+}
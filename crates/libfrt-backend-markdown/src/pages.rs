@@ -0,0 +1,389 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use crate::markdown::{escape, escape_text, image, link};
+use crate::profile::ProfileMarkdown;
+use libfrt::backend::relative_root as root;
+use libfrt::entries::game::{Description, Game, GameState};
+use libfrt::entries::link::Link;
+use libfrt::entries::media::{Image, Media};
+use libfrt::i18n::LangId;
+use libfrt::utils::collate::CollationKey;
+use libfrt::ContextData;
+
+/// Renders the files of a single language
+pub struct PageContext<'a> {
+    pub data: &'a ContextData,
+    pub profile: &'a ProfileMarkdown,
+    pub lang: LangId,
+}
+
+impl PageContext<'_> {
+    fn ui(&self, k: &str) -> &str {
+        self.data.ui(self.lang, k)
+    }
+
+    fn game_uri(&self, depth: usize, id: &str) -> String {
+        format!("{}/{}/games/{}.md", root(depth), self.lang.as_str(), id)
+    }
+
+    fn facet_list(&self, namespace: &str, values: &[&str]) -> String {
+        values
+            .iter()
+            .map(|v| self.data.facet_label(self.lang, namespace, v))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Sort games by localized name and then id
+    fn sort(&self, games: &mut [&Game]) {
+        games.sort_by_cached_key(|g| {
+            (
+                CollationKey::new(g.localized_name(self.lang), self.lang),
+                g.id.clone(),
+            )
+        });
+    }
+
+    /// Listed games, sorted by localized name and then id
+    fn listed(&self) -> Vec<&Game> {
+        let mut games: Vec<&Game> = self
+            .data
+            .games
+            .values()
+            .filter(|g| g.state.is_listed())
+            .map(|g| g.as_ref())
+            .collect();
+        self.sort(&mut games);
+        games
+    }
+
+    /// All files of the language, with paths relative to the language directory
+    pub fn render(&self) -> Result<Vec<(String, String)>> {
+        let listed = self.listed();
+
+        let mut index = String::new();
+        writeln!(index, "# {}\n", escape(self.ui("main.title")))?;
+        writeln!(index, "{}\n", escape_text(self.ui("main.description")))?;
+        writeln!(
+            index,
+            "{} · {}\n",
+            link("tags/README.md", self.ui("nav.tags")),
+            link("../README.md", self.ui("nav.languages"))
+        )?;
+        index.push_str(&self.table(1, &listed)?);
+
+        let mut files = vec![(String::from("README.md"), index)];
+        files.extend(self.tags()?);
+
+        let mut games: Vec<_> = self.data.games.values().collect();
+        games.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        for game in games {
+            files.push((format!("games/{}.md", game.id), self.game(game)?));
+        }
+
+        Ok(files)
+    }
+
+    fn table(&self, depth: usize, games: &[&Game]) -> Result<String> {
+        let mut ret = String::new();
+
+        let mut columns = vec![
+            self.ui("list.by_name"),
+            self.ui("game.platforms"),
+            self.ui("game.release_status"),
+            self.ui("game.release_date"),
+        ];
+        if self.profile.index_thumbnails {
+            columns.insert(0, "");
+        }
+
+        writeln!(
+            ret,
+            "| {} |",
+            columns.iter().map(|c| escape(c)).collect::<Vec<_>>().join(" | ")
+        )?;
+        writeln!(ret, "|{}", " --- |".repeat(columns.len()))?;
+
+        for game in games {
+            let mut cells = Vec::new();
+
+            if self.profile.index_thumbnails {
+                cells.push(match &game.thumbnail {
+                    Some(thumbnail) => format!(
+                        r#"<img src="{}" alt="" width="120">"#,
+                        game.image_uri(&root(depth), thumbnail)?
+                    ),
+                    None => String::new(),
+                });
+            }
+
+            let platforms: Vec<&str> = game.platforms.iter().map(|p| p.as_str()).collect();
+            cells.push(link(
+                &self.game_uri(depth, &game.id),
+                game.localized_name(self.lang),
+            ));
+            cells.push(escape(&self.facet_list("platform", &platforms)));
+            cells.push(match &game.release_status {
+                Some(status) => escape(&self.facet_list("release-status", &[status.as_str()])),
+                None => String::new(),
+            });
+            cells.push(match &game.release_date {
+                Some(date) => date.to_string(),
+                None => String::new(),
+            });
+
+            writeln!(ret, "| {} |", cells.join(" | "))?;
+        }
+
+        Ok(ret)
+    }
+
+    fn tags(&self) -> Result<Vec<(String, String)>> {
+        let mut tags = self.data.facets_index(false);
+        for games in tags.values_mut() {
+            self.sort(games);
+        }
+
+        let mut files = Vec::new();
+
+        let mut index = String::new();
+        writeln!(index, "# {}", escape(self.ui("nav.tags")))?;
+
+        let mut last_namespace = "";
+        for ((namespace, value), games) in tags.iter() {
+            if namespace != last_namespace {
+                let title = self.ui(&format!("facet_namespaces.{}", namespace));
+                writeln!(index, "\n## {}\n", escape(title))?;
+                last_namespace = namespace;
+            }
+
+            let label = self.data.facet_label(self.lang, namespace, value);
+            writeln!(
+                index,
+                "- {} ({})",
+                link(&format!("{}/{}.md", namespace, value), &label),
+                games.len()
+            )?;
+
+            let mut page = String::new();
+            writeln!(page, "# {}\n", escape(&label))?;
+            page.push_str(&self.table(3, games)?);
+            writeln!(page, "\n{}", link("../README.md", self.ui("nav.tags")))?;
+
+            files.push((format!("tags/{}/{}.md", namespace, value), page));
+        }
+
+        writeln!(index, "\n{}", link("../README.md", self.ui("nav.index")))?;
+        files.push((String::from("tags/README.md"), index));
+
+        Ok(files)
+    }
+
+    fn game(&self, game: &Game) -> Result<String> {
+        let mut ret = String::new();
+        let name = game.localized_name(self.lang);
+        writeln!(ret, "# {}\n", escape(name))?;
+
+        match &game.state {
+            GameState::Expunged { .. } => {
+                writeln!(ret, "{}\n", escape(self.ui("tombstone.expunged")))?;
+                return self.footer(ret);
+            }
+            GameState::Redirected { target } => {
+                let target_name = self.data.game_name(target, self.lang);
+                writeln!(
+                    ret,
+                    "{} {}\n",
+                    escape(self.ui("tombstone.redirected")),
+                    link(&self.game_uri(2, target), target_name)
+                )?;
+                return self.footer(ret);
+            }
+            GameState::Delisted { .. } => {
+                writeln!(ret, "> {}\n", escape(self.ui("warnbox.delisted")))?;
+            }
+            GameState::Active => {}
+        }
+
+        if let Some(thumbnail) = &game.thumbnail {
+            writeln!(ret, "{}\n", image(&game.image_uri(&root(2), thumbnail)?, name))?;
+        }
+
+        if let Some(brief) = game.localized_brief_description(self.lang) {
+            writeln!(ret, "> {}\n", escape(brief))?;
+        }
+
+        match game.localized_description(self.lang) {
+            Description::Plain(s) => writeln!(ret, "{}\n", escape_text(s))?,
+            Description::Markdown(s) => writeln!(ret, "{}\n", s.trim())?,
+        }
+
+        let mut facts = Vec::new();
+        if !game.platforms.is_empty() {
+            let platforms: Vec<&str> = game.platforms.iter().map(|p| p.as_str()).collect();
+            facts.push((
+                self.ui("game.platforms"),
+                self.facet_list("platform", &platforms),
+            ));
+        }
+        if let Some(status) = &game.release_status {
+            facts.push((
+                self.ui("game.release_status"),
+                self.facet_list("release-status", &[status.as_str()]),
+            ));
+        }
+        if let Some(date) = &game.release_date {
+            facts.push((self.ui("game.release_date"), date.to_string()));
+        }
+        if let Some(monetization) = &game.monetization {
+            facts.push((
+                self.ui("game.monetization"),
+                self.facet_list("monetization", &[monetization.as_str()]),
+            ));
+        }
+        if !game.languages.is_empty() {
+            let languages: Vec<&str> = game.languages.iter().map(|l| l.as_str()).collect();
+            facts.push((
+                self.ui("game.languages"),
+                self.facet_list("language", &languages),
+            ));
+        }
+        for (key, value) in facts.iter() {
+            writeln!(ret, "- **{}**: {}", escape(key), escape(value))?;
+        }
+
+        let mut last_category = None;
+        for l in Link::displayed(&game.links, self.lang) {
+            if last_category != Some(l.category()) {
+                let title = self.ui(&format!("link_categories.{}", l.category().as_str()));
+                writeln!(ret, "\n## {}\n", escape(title))?;
+                last_category = Some(l.category());
+            }
+
+            write!(ret, "- {}", link(&l.uri, l.localized_label(self.lang)))?;
+            if let Some(patch) = &l.patch {
+                let mut info = self.data.facet_label(self.lang, "language", &patch.lang);
+                if let Some(author) = &patch.author {
+                    write!(info, ", {} {}", self.ui("game.patch_author"), author)?;
+                }
+                if let Some(completeness) = patch.completeness {
+                    write!(
+                        info,
+                        ", {} {}%",
+                        self.ui("game.patch_completeness"),
+                        completeness
+                    )?;
+                }
+                write!(ret, " ({})", escape(&info))?;
+            }
+            writeln!(ret)?;
+        }
+
+        let medias: Vec<_> = game
+            .medias
+            .iter()
+            .filter(|m| !(m.sensitive && self.profile.hide_sensitive_media))
+            .collect();
+
+        if !medias.is_empty() {
+            if !ret.ends_with("\n\n") {
+                ret.push('\n');
+            }
+            writeln!(ret, "## {}\n", escape(self.ui("game.screenshots")))?;
+        }
+
+        for gm in medias {
+            if gm.sensitive {
+                // keep sensitive images behind a link, instead of inlining them
+                write!(ret, "{} ", escape(self.ui("sensitive.label")))?;
+            }
+
+            let show_image = |image: &Image| -> Result<String> {
+                let uri = game.image_uri(&root(2), image)?;
+                let alt = image.alt.get(self.lang).unwrap_or(self.ui("media.image"));
+                let mut s = match gm.sensitive {
+                    true => link(&uri, alt),
+                    false => crate::markdown::image(&uri, alt),
+                };
+                if let Some(caption) = image.caption.get(self.lang) {
+                    write!(s, "  \n*{}*", escape(caption))?;
+                }
+                Ok(s)
+            };
+
+            match &gm.media {
+                Media::Image(i) => writeln!(ret, "{}\n", show_image(i)?)?,
+                Media::HBox(images) => {
+                    for i in images {
+                        writeln!(ret, "{}\n", show_image(i)?)?;
+                    }
+                }
+                Media::Youtube(id) => writeln!(
+                    ret,
+                    "{}\n",
+                    link(
+                        &format!("https://www.youtube.com/watch?v={}", id),
+                        self.ui("media.youtube")
+                    )
+                )?,
+                Media::Video { sources, .. } => {
+                    let sources: Vec<String> = sources
+                        .iter()
+                        .map(|s| {
+                            link(
+                                &game.file_uri(&root(2), &s.uri, s.remote),
+                                &format!("{} ({})", self.ui("media.video"), s.mime),
+                            )
+                        })
+                        .collect();
+                    writeln!(ret, "{}\n", sources.join(" · "))?;
+                }
+            }
+        }
+
+        if !game.relations.is_empty() {
+            if !ret.ends_with("\n\n") {
+                ret.push('\n');
+            }
+            writeln!(ret, "## {}\n", escape(self.ui("game.related")))?;
+        }
+        for relation in game.relations.iter() {
+            let target_name = self.data.game_name(&relation.target, self.lang);
+            writeln!(
+                ret,
+                "- {}: {}",
+                escape(self.ui(&format!("relations.{}", relation.kind.as_str()))),
+                link(&self.game_uri(2, &relation.target), target_name)
+            )?;
+        }
+
+        self.footer(ret)
+    }
+
+    fn footer(&self, mut s: String) -> Result<String> {
+        s.truncate(s.trim_end().len());
+        writeln!(s, "\n\n---\n\n{}", link("../README.md", self.ui("nav.index")))?;
+        Ok(s)
+    }
+}
+
+/// The catalog root, linking to the index of every language
+pub fn root_index(data: &ContextData, langs: &[LangId]) -> Result<String> {
+    let mut ret = String::new();
+    writeln!(ret, "# {}\n", escape(data.ui(LangId::default(), "main.title")))?;
+
+    for lang in langs {
+        writeln!(
+            ret,
+            "- {}",
+            link(
+                &format!("{}/README.md", lang.as_str()),
+                &data.facet_label(*lang, "language", &lang.as_str())
+            )
+        )?;
+    }
+
+    Ok(ret)
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use libfrt::backend::registry::BackendConfigKey;
+use serde::Deserialize;
+
+/// Keys of `ProfileMarkdown`, for `frt backends`
+pub const CONFIG_KEYS: &[BackendConfigKey] = &[
+    BackendConfigKey {
+        key: "index_thumbnails",
+        kind: "bool",
+        default: "false",
+        description: "Show thumbnails in the game tables",
+    },
+    BackendConfigKey {
+        key: "hide_sensitive_media",
+        kind: "bool",
+        default: "false",
+        description: "Leave out sensitive medias, instead of marking them",
+    },
+];
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ProfileMarkdown {
+    pub index_thumbnails: bool,
+
+    pub hide_sensitive_media: bool,
+}
+
+impl ProfileMarkdown {
+    pub fn from_value(value: toml::Value) -> Result<Self> {
+        Ok(ProfileMarkdown::deserialize(value)?)
+    }
+}
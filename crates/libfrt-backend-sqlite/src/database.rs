@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use rusqlite::{params, Transaction};
+
+use libfrt::entries::game::{Description, Game, GameState};
+use libfrt::entries::media::{Image, ImageSource, Media};
+use libfrt::i18n::LangId;
+
+pub const SCHEMA: &str = include_str!("schema.sql");
+
+/// Version of `SCHEMA`, stored as `PRAGMA user_version`
+pub const SCHEMA_VERSION: u32 = 2;
+
+fn description(description: &Description) -> (&str, &'static str) {
+    match description {
+        Description::Plain(s) => (s, "plain"),
+        Description::Markdown(s) => (s, "markdown"),
+    }
+}
+
+/// Languages of `maps` other than the default one, sorted by tag
+fn l10n_langs<'a, I>(maps: I) -> Vec<LangId>
+where
+    I: IntoIterator<Item = &'a HashMap<LangId, String>>,
+{
+    let mut langs: Vec<LangId> = maps
+        .into_iter()
+        .flat_map(|m| m.keys().copied())
+        .filter(|l| *l != LangId::default())
+        .collect();
+    langs.sort_unstable_by_key(|l| l.as_str());
+    langs.dedup();
+    langs
+}
+
+fn image_source(image: &Image) -> (&str, &'static str) {
+    match &image.source {
+        ImageSource::Bundled(s) => (s, "bundled"),
+        ImageSource::Remote(s) => (s, "remote"),
+        ImageSource::LocalShared(s) => (s, "shared"),
+    }
+}
+
+/// Writes the rows of games in a single transaction
+pub struct Writer<'a> {
+    pub tx: &'a Transaction<'a>,
+    /// Languages of the full-text index
+    pub langs: &'a [LangId],
+}
+
+impl Writer<'_> {
+    pub fn game(&self, game: &Game) -> Result<()> {
+        let (text, format) = description(&game.description);
+        let redirect = match &game.state {
+            GameState::Redirected { target } => Some(target.as_str()),
+            _ => None,
+        };
+
+        self.tx.execute(
+            "INSERT INTO games VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                game.id,
+                game.name,
+                text,
+                format,
+                game.brief_description,
+                game.state.as_str(),
+                redirect,
+                game.rating.as_str(),
+                game.release_status.as_ref().map(|s| s.as_str()),
+                game.release_date.as_ref().map(|d| d.to_string()),
+                game.monetization.as_ref().map(|m| m.as_str()),
+                game.thumbnail.as_ref().map(|t| image_source(t).0),
+                game.added,
+                game.updated,
+            ],
+        )?;
+
+        let mut langs: Vec<_> = game.l10n.iter().collect();
+        langs.sort_unstable_by_key(|(lang, _)| lang.as_str());
+        for (lang, l10n) in langs {
+            let description = l10n.description.as_ref().map(description);
+            self.tx.execute(
+                "INSERT INTO game_l10n VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    game.id,
+                    lang.as_str(),
+                    l10n.name,
+                    description.map(|d| d.0),
+                    description.map(|d| d.1),
+                    l10n.brief_description,
+                ],
+            )?;
+        }
+
+        for lang in self.langs {
+            self.tx.execute(
+                "INSERT INTO games_fts VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    game.id,
+                    lang.as_str(),
+                    game.localized_name(*lang),
+                    game.localized_brief_description(*lang),
+                    description(game.localized_description(*lang)).0,
+                ],
+            )?;
+        }
+
+        self.links(game)?;
+        self.medias(game)?;
+
+        for (position, author) in game.authors.iter().enumerate() {
+            self.tx.execute(
+                "INSERT INTO authors VALUES (?1, ?2, ?3, ?4)",
+                params![game.id, position, author.name, author.standalone],
+            )?;
+            for role in author.roles.iter() {
+                self.tx.execute(
+                    "INSERT INTO author_roles VALUES (?1, ?2, ?3)",
+                    params![game.id, position, role],
+                )?;
+            }
+        }
+
+        let mut facets = game.facets();
+        facets.sort_unstable();
+        facets.dedup();
+        for facet in facets {
+            if let Some((namespace, value)) = facet.split_once(':') {
+                self.tx.execute(
+                    "INSERT INTO tags VALUES (?1, ?2, ?3)",
+                    params![game.id, namespace, value],
+                )?;
+            }
+        }
+
+        for relation in game.relations.iter() {
+            self.tx.execute(
+                "INSERT INTO relations VALUES (?1, ?2, ?3)",
+                params![game.id, relation.kind.as_str(), relation.target],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn links(&self, game: &Game) -> Result<()> {
+        for (position, link) in game.links.iter().enumerate() {
+            let patch = link.patch.as_ref();
+            self.tx.execute(
+                "INSERT INTO links VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    game.id,
+                    position,
+                    link.uri,
+                    link.rule.as_ref().map(|r| r.name.as_str()),
+                    link.category().as_str(),
+                    link.localized_label(LangId::default()),
+                    patch.map(|p| p.lang.as_str()),
+                    patch.and_then(|p| p.author.as_deref()),
+                    patch.and_then(|p| p.completeness),
+                    link.hint_problem.as_ref().map(|p| p.message.as_str()),
+                ],
+            )?;
+
+            for lang in l10n_langs([&link.label]) {
+                self.tx.execute(
+                    "INSERT INTO link_l10n VALUES (?1, ?2, ?3, ?4)",
+                    params![game.id, position, lang.as_str(), link.label[&lang]],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn image(
+        &self,
+        game: &Game,
+        position: usize,
+        item: usize,
+        kind: &str,
+        sensitive: bool,
+        image: &Image,
+    ) -> Result<()> {
+        let (uri, source) = image_source(image);
+        self.tx.execute(
+            "INSERT INTO medias VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, NULL, ?8, ?9)",
+            params![
+                game.id,
+                position,
+                item,
+                kind,
+                sensitive,
+                source,
+                uri,
+                image.caption.get(LangId::default()),
+                image.alt.get(LangId::default()),
+            ],
+        )?;
+
+        for lang in l10n_langs([&image.caption.0, &image.alt.0]) {
+            self.tx.execute(
+                "INSERT INTO media_l10n VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    game.id,
+                    position,
+                    item,
+                    lang.as_str(),
+                    image.caption.0.get(&lang),
+                    image.alt.0.get(&lang),
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn medias(&self, game: &Game) -> Result<()> {
+        for (position, gm) in game.medias.iter().enumerate() {
+            match &gm.media {
+                Media::Image(image) => {
+                    self.image(game, position, 0, "image", gm.sensitive, image)?;
+                }
+                Media::HBox(images) => {
+                    for (item, image) in images.iter().enumerate() {
+                        self.image(game, position, item, "hbox", gm.sensitive, image)?;
+                    }
+                }
+                Media::Youtube(id) => {
+                    self.tx.execute(
+                        "INSERT INTO medias VALUES (?1, ?2, 0, 'youtube', ?3, 'remote', ?4, NULL, NULL, NULL)",
+                        params![game.id, position, gm.sensitive, id],
+                    )?;
+                }
+                Media::Video { sources, .. } => {
+                    for (item, source) in sources.iter().enumerate() {
+                        self.tx.execute(
+                            "INSERT INTO medias VALUES (?1, ?2, ?3, 'video', ?4, ?5, ?6, ?7, NULL, NULL)",
+                            params![
+                                game.id,
+                                position,
+                                item,
+                                gm.sensitive,
+                                if source.remote { "remote" } else { "bundled" },
+                                source.uri,
+                                source.mime,
+                            ],
+                        )?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
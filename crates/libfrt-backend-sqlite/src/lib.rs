@@ -0,0 +1,200 @@
+mod database;
+mod profile;
+
+#[macro_use]
+extern crate log;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use rusqlite::Connection;
+
+use database::{Writer, SCHEMA, SCHEMA_VERSION};
+use libfrt::backend::registry::BackendRegistration;
+use libfrt::backend::{clean_output_dir, Backend, BackendArguments};
+use libfrt::i18n::LangId;
+use libfrt::profile::Profile;
+use libfrt::utils::fs::ensure_dir;
+use libfrt::ContextData;
+use profile::ProfileSQLite;
+
+/// Registration of the SQLite backend
+pub fn registration() -> BackendRegistration {
+    BackendRegistration {
+        name: "sqlite",
+        description: "Normalized SQLite database of the catalog, for ad-hoc queries",
+        constructor: Some(|value| Ok(Box::new(BackendSQLite::new(value)?))),
+        config: profile::CONFIG_KEYS,
+    }
+}
+
+/// Writes the catalog into a single SQLite database, see `schema.sql` for
+/// the tables. The database is created from scratch on every build.
+pub struct BackendSQLite {
+    pub profile: ProfileSQLite,
+
+    output: Option<PathBuf>,
+    langs: Vec<LangId>,
+}
+
+impl BackendSQLite {
+    pub fn new(value: Option<toml::Value>) -> Result<Self> {
+        Ok(Self {
+            profile: match value {
+                Some(value) => ProfileSQLite::from_value(value)?,
+                None => ProfileSQLite::default(),
+            },
+
+            output: None,
+            langs: Vec::new(),
+        })
+    }
+}
+
+impl Backend for BackendSQLite {
+    fn resync(
+        &mut self,
+        _profile: &Profile,
+        data: &mut ContextData,
+        args: &BackendArguments,
+    ) -> Result<()> {
+        self.output = clean_output_dir(args)?;
+        self.langs = data.langs();
+
+        Ok(())
+    }
+
+    fn render(&self, _profile: &Profile, data: &ContextData) -> Result<BackendArguments> {
+        let Some(output_dir) = &self.output else {
+            libfrt::bail!(
+                InvalidArgument,
+                "SQLite backend requires file-system output"
+            )
+        };
+
+        let path = output_dir.join(&self.profile.filename);
+        ensure_dir(&path)?;
+
+        let mut conn = Connection::open(&path)?;
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+        let games = data.exported_games(self.profile.include_unlisted);
+
+        info!("Write {} games to '{}'", games.len(), path.display());
+
+        let tx = conn.transaction()?;
+        let writer = Writer {
+            tx: &tx,
+            langs: &self.langs,
+        };
+        for game in games {
+            writer.game(game)?;
+        }
+        tx.commit()?;
+
+        Ok(BackendArguments::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libfrt::testing::Fixture;
+    use rusqlite::Connection;
+
+    use super::BackendSQLite;
+
+    fn render(fixture: &mut Fixture) -> Connection {
+        fixture.render(&mut BackendSQLite::new(None).unwrap());
+
+        Connection::open(fixture.output().join("catalog.sqlite")).unwrap()
+    }
+
+    fn strings(conn: &Connection, sql: &str) -> Vec<Option<String>> {
+        let mut stmt = conn.prepare(sql).unwrap();
+        let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+        rows.collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn test_database() {
+        let mut fixture = Fixture::new("sqlite");
+        let conn = render(&mut fixture);
+
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, super::SCHEMA_VERSION);
+
+        assert_eq!(
+            strings(
+                &conn,
+                "SELECT game_id || ':' || lang FROM games_fts WHERE games_fts MATCH 'name:富游戏'"
+            ),
+            [Some("foo-game:zh-cn".to_owned())]
+        );
+        assert_eq!(
+            strings(&conn, "SELECT DISTINCT game_id FROM games_fts WHERE games_fts MATCH 'name:foo' ORDER BY game_id"),
+            [Some("foo-game".to_owned()), Some("old-foo".to_owned())]
+        );
+
+        assert_eq!(
+            strings(
+                &conn,
+                "SELECT rule FROM links WHERE game_id = 'foo-game' ORDER BY position"
+            ),
+            [Some("steam".to_owned()), Some("website".to_owned()), None]
+        );
+        assert_eq!(
+            strings(&conn, "SELECT game_id FROM tags WHERE namespace = 'platform' AND value = 'linux' ORDER BY game_id"),
+            [Some("foo-game".to_owned()), Some("hidden-game".to_owned())]
+        );
+        assert_eq!(
+            strings(&conn, "SELECT game_id FROM relations WHERE kind = 'sequel'"),
+            [Some("foo-game".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_l10n() {
+        let mut fixture = Fixture::new("sqlite-l10n");
+        let conn = render(&mut fixture);
+
+        assert_eq!(
+            strings(&conn, "SELECT label FROM link_l10n WHERE game_id = 'foo-game' AND position = 0 AND lang = 'zh-cn'"),
+            [Some("在 Steam 上获取".to_owned())]
+        );
+        assert_eq!(
+            strings(
+                &conn,
+                "SELECT caption FROM medias WHERE game_id = 'foo-game' AND position = 0"
+            ),
+            [Some("Title screen".to_owned())]
+        );
+        assert_eq!(
+            strings(&conn, "SELECT caption || '/' || coalesce(alt, '') FROM media_l10n WHERE game_id = 'foo-game'"),
+            [Some("标题画面/".to_owned())]
+        );
+        assert_eq!(
+            strings(
+                &conn,
+                "SELECT alt FROM medias WHERE game_id = 'foo-game' AND kind = 'hbox' ORDER BY item"
+            ),
+            [None, Some("Map".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_exclude_sensitive() {
+        let mut fixture = Fixture::new("sqlite-sfw");
+        fixture.data.exclude_sensitive();
+        let conn = render(&mut fixture);
+
+        assert_eq!(
+            strings(&conn, "SELECT target FROM relations WHERE game_id = 'bar-game'"),
+            [Some("foo-game".to_owned())]
+        );
+        drop(conn);
+        fixture.assert_sensitive_excluded();
+    }
+}
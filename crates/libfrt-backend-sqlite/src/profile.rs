@@ -0,0 +1,38 @@
+use anyhow::Result;
+use libfrt::backend::registry::{BackendConfigKey, INCLUDE_UNLISTED};
+use serde::Deserialize;
+
+/// Keys of `ProfileSQLite`, for `frt backends`
+pub const CONFIG_KEYS: &[BackendConfigKey] = &[
+    BackendConfigKey {
+        key: "filename",
+        kind: "string",
+        default: "catalog.sqlite",
+        description: "Name of the database file in the output directory",
+    },
+    INCLUDE_UNLISTED,
+];
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ProfileSQLite {
+    pub filename: String,
+
+    /// See `ContextData::exported_games`
+    pub include_unlisted: bool,
+}
+
+impl Default for ProfileSQLite {
+    fn default() -> Self {
+        Self {
+            filename: String::from("catalog.sqlite"),
+            include_unlisted: true,
+        }
+    }
+}
+
+impl ProfileSQLite {
+    pub fn from_value(value: toml::Value) -> Result<Self> {
+        Ok(ProfileSQLite::deserialize(value)?)
+    }
+}
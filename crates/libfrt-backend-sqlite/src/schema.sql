@@ -0,0 +1,135 @@
+-- Schema of the catalog database, regenerated on every build.
+-- Texts of the default language are stored in `games`, `links` and
+-- `medias`, translations in the `*_l10n` tables next to them. Lists keep
+-- the order of the sources in `position`.
+
+CREATE TABLE games (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    -- 'plain' or 'markdown'
+    description_format TEXT NOT NULL,
+    brief_description TEXT,
+    -- 'active', 'delisted', 'expunged' or 'redirected'
+    state TEXT NOT NULL,
+    -- Target of a redirected game
+    redirect TEXT,
+    rating TEXT NOT NULL,
+    release_status TEXT,
+    release_date TEXT,
+    monetization TEXT,
+    thumbnail TEXT,
+    -- Unix timestamps
+    added INTEGER,
+    updated INTEGER
+);
+
+CREATE TABLE game_l10n (
+    game_id TEXT NOT NULL REFERENCES games(id),
+    lang TEXT NOT NULL,
+    name TEXT,
+    description TEXT,
+    description_format TEXT,
+    brief_description TEXT,
+    PRIMARY KEY (game_id, lang)
+);
+
+CREATE TABLE links (
+    game_id TEXT NOT NULL REFERENCES games(id),
+    position INTEGER NOT NULL,
+    uri TEXT NOT NULL,
+    -- Name of the resolved stock link rule, NULL for custom links
+    rule TEXT,
+    category TEXT NOT NULL,
+    -- Label in the default language
+    label TEXT NOT NULL,
+    -- Localization patch
+    patch_lang TEXT,
+    patch_author TEXT,
+    patch_completeness INTEGER,
+    -- Problem found while checking the `.rule` hint
+    hint_problem TEXT,
+    PRIMARY KEY (game_id, position)
+);
+
+CREATE TABLE link_l10n (
+    game_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    lang TEXT NOT NULL,
+    label TEXT NOT NULL,
+    PRIMARY KEY (game_id, position, lang),
+    FOREIGN KEY (game_id, position) REFERENCES links(game_id, position)
+);
+
+CREATE TABLE medias (
+    game_id TEXT NOT NULL REFERENCES games(id),
+    position INTEGER NOT NULL,
+    -- Index of the image in a box, or of the video source
+    item INTEGER NOT NULL,
+    -- 'image', 'hbox', 'youtube' or 'video'
+    kind TEXT NOT NULL,
+    sensitive INTEGER NOT NULL,
+    -- 'bundled', 'remote' or 'shared'; YouTube ids are 'remote'
+    source TEXT NOT NULL,
+    uri TEXT NOT NULL,
+    -- MIME type of video sources
+    mime TEXT,
+    -- Caption and alternative text in the default language
+    caption TEXT,
+    alt TEXT,
+    PRIMARY KEY (game_id, position, item)
+);
+
+CREATE TABLE media_l10n (
+    game_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    item INTEGER NOT NULL,
+    lang TEXT NOT NULL,
+    caption TEXT,
+    alt TEXT,
+    PRIMARY KEY (game_id, position, item, lang),
+    FOREIGN KEY (game_id, position, item) REFERENCES medias(game_id, position, item)
+);
+
+CREATE TABLE authors (
+    game_id TEXT NOT NULL REFERENCES games(id),
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    -- Whether the author has an entry of its own
+    standalone INTEGER NOT NULL,
+    PRIMARY KEY (game_id, position)
+);
+
+CREATE TABLE author_roles (
+    game_id TEXT NOT NULL REFERENCES games(id),
+    position INTEGER NOT NULL,
+    role TEXT NOT NULL,
+    FOREIGN KEY (game_id, position) REFERENCES authors(game_id, position)
+);
+
+-- Facets of the games, e.g. namespace 'platform' and value 'linux'
+CREATE TABLE tags (
+    game_id TEXT NOT NULL REFERENCES games(id),
+    namespace TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (game_id, namespace, value)
+);
+
+CREATE TABLE relations (
+    game_id TEXT NOT NULL REFERENCES games(id),
+    kind TEXT NOT NULL,
+    target TEXT NOT NULL
+);
+
+CREATE INDEX links_rule ON links(rule);
+CREATE INDEX tags_value ON tags(namespace, value);
+CREATE INDEX authors_name ON authors(name);
+
+-- One row per game and UI language, with the localized texts
+CREATE VIRTUAL TABLE games_fts USING fts5(
+    game_id UNINDEXED,
+    lang UNINDEXED,
+    name,
+    brief_description,
+    description
+);
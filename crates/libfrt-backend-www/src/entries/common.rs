@@ -1,13 +1,17 @@
 use std::borrow::Cow;
 use std::collections::{BinaryHeap, HashMap};
+use std::rc::Rc;
 
 use anyhow::Result;
 use serde::Serialize;
 
+use crate::rc::RenderContext;
 use crate::utils::{uri, xml};
 use crate::BackendWWW;
 use libfrt::entries::game::Game;
-use libfrt::entries::media::{Image, ImageSource};
+use libfrt::entries::media::{Image, Media};
+use libfrt::entries::Bundle;
+use libfrt::i18n::{L10nText, LangId};
 
 #[derive(Serialize, Debug)]
 pub struct HtmlText {
@@ -95,6 +99,10 @@ pub struct HtmlImageSrc {
 }
 
 impl HtmlImageSrc {
+    pub fn new(src: String, remote: bool) -> Self {
+        Self { remote, src }
+    }
+
     pub fn to_str<'a, S>(&'a self, rr: S) -> Cow<'a, str>
     where
         S: AsRef<str>,
@@ -129,7 +137,8 @@ impl PartialOrd for HtmlImageCondition {
 pub struct HtmlImage {
     sources: BinaryHeap<HtmlImageCondition>,
     size: Option<(usize, usize)>,
-    captain: Option<String>,
+    caption: L10nText,
+    alt: L10nText,
     extra_queries: HashMap<String, String>,
 }
 
@@ -138,7 +147,8 @@ impl HtmlImage {
         Self {
             sources: BinaryHeap::default(),
             size: image.size.clone(),
-            captain: image.captain.clone(),
+            caption: image.caption.clone(),
+            alt: image.alt.clone(),
             extra_queries: HashMap::default(),
         }
     }
@@ -171,6 +181,31 @@ impl HtmlImage {
         Ok(())
     }
 
+    /// Source of the most preferred format, for places taking a single URL
+    pub fn preferred_src<S>(&self, rr: S) -> Option<String>
+    where
+        S: AsRef<str>,
+    {
+        self.sources.peek().map(|c| c.srcset.to_str(rr).into_owned())
+    }
+
+    /// Image with localized alt text, wrapped in `<figure>` if it has a caption
+    pub fn figure_html<S>(&self, rr: S, lang: LangId) -> Result<String>
+    where
+        S: AsRef<str>,
+    {
+        let picture = self.html(rr, "", self.alt.get(lang))?;
+
+        Ok(match self.caption.get(lang) {
+            Some(caption) => format!(
+                "<figure>{}<figcaption>{}</figcaption></figure>",
+                picture,
+                xml::escape_str(caption)
+            ),
+            None => picture,
+        })
+    }
+
     pub fn html<S, D>(&self, rr: S, node_classes: D, alt: Option<&str>) -> Result<String>
     where
         S: AsRef<str>,
@@ -184,10 +219,7 @@ impl HtmlImage {
 
         if let Some(alt) = match alt {
             Some(alt) => Some(alt),
-            None => match self.captain.as_ref() {
-                Some(alt) => Some(alt.as_str()),
-                None => None,
-            },
+            None => self.alt.get(LangId::default()),
         } {
             node.push_str(format!(r#"alt="{}" "#, xml::escape_str(alt)).as_str())
         }
@@ -241,18 +273,161 @@ impl HtmlImage {
     }
 }
 
-pub struct HtmlMedia {
-    pub html: String,
+pub struct HtmlCaption {
+    lang: String,
+    label: Option<String>,
+    src: HtmlImageSrc,
 }
 
-impl HtmlMedia {
-    pub fn from_image(game: &Game, backend: &BackendWWW, image: &Image) -> Result<HtmlMedia> {
-        match &image.source {
-            ImageSource::LocalShared(_) => todo!(),
-            ImageSource::Bundled(file_name) => {}
-            ImageSource::Remote(uri) => {}
+pub struct HtmlVideo {
+    /// Sources with MIME type, in order of preference
+    sources: Vec<(HtmlImageSrc, String)>,
+    poster: Option<HtmlImage>,
+    captions: Vec<HtmlCaption>,
+}
+
+impl HtmlVideo {
+    pub fn html<S>(&self, rc: &RenderContext, rr: S) -> Result<String>
+    where
+        S: AsRef<str>,
+    {
+        let rr = rr.as_ref();
+        let mut result = String::from(r#"<video controls preload="none""#);
+
+        if let Some(poster) = self.poster.as_ref().and_then(|p| p.preferred_src(rr)) {
+            result.push_str(&format!(r#" poster="{}""#, xml::escape_str(poster)));
+        }
+
+        result.push('>');
+
+        for (src, mime) in self.sources.iter() {
+            result.push_str(&format!(
+                r#"<source src="{}" type="{}">"#,
+                xml::escape_str(src.to_str(rr)),
+                xml::escape_str(mime)
+            ));
         }
 
-        todo!("")
+        let mut default = false;
+        for caption in self.captions.iter() {
+            let label = match &caption.label {
+                Some(label) => label.clone(),
+                None => rc.facet_label("language", &caption.lang),
+            };
+
+            // captions in the language of the page are shown by default
+            let is_default = !default && LangId::from_tag(&caption.lang) == Some(rc.lang);
+            default |= is_default;
+
+            result.push_str(&format!(
+                r#"<track kind="captions" src="{}" srclang="{}" label="{}"{}>"#,
+                xml::escape_str(caption.src.to_str(rr)),
+                xml::escape_str(&caption.lang),
+                xml::escape_str(label),
+                if is_default { " default" } else { "" }
+            ));
+        }
+
+        result.push_str("</video>");
+        Ok(result)
+    }
+}
+
+pub enum HtmlMedia {
+    Image(HtmlImage),
+    HBox(Vec<HtmlImage>),
+    Video(HtmlVideo),
+    /// YouTube video id, rendered as a click-to-load facade
+    Youtube(String),
+}
+
+impl HtmlMedia {
+    fn import_src(
+        backend: &BackendWWW,
+        game: &Rc<Game>,
+        uri: &str,
+        remote: bool,
+    ) -> Result<HtmlImageSrc> {
+        Ok(match remote {
+            true => HtmlImageSrc::new(uri.to_owned(), true),
+            false => HtmlImageSrc::new(
+                backend.import_file(uri, game.clone() as Rc<dyn Bundle>)?,
+                false,
+            ),
+        })
+    }
+
+    /// Cook a media of a game
+    pub fn from_media(game: Rc<Game>, backend: &BackendWWW, media: &Media) -> Result<HtmlMedia> {
+        Ok(match media {
+            Media::Image(image) => {
+                HtmlMedia::Image(backend.import_image(image, game as Rc<dyn Bundle>)?)
+            }
+            Media::HBox(images) => {
+                let mut result = Vec::new();
+                for image in images.iter() {
+                    result.push(backend.import_image(image, game.clone() as Rc<dyn Bundle>)?);
+                }
+                HtmlMedia::HBox(result)
+            }
+            Media::Video {
+                sources,
+                poster,
+                captions,
+            } => {
+                let mut video = HtmlVideo {
+                    sources: Vec::new(),
+                    poster: match poster {
+                        Some(poster) => {
+                            Some(backend.import_image(poster, game.clone() as Rc<dyn Bundle>)?)
+                        }
+                        None => None,
+                    },
+                    captions: Vec::new(),
+                };
+
+                for source in sources.iter() {
+                    let src = Self::import_src(backend, &game, &source.uri, source.remote)?;
+                    video.sources.push((src, source.mime.clone()));
+                }
+
+                for caption in captions.iter() {
+                    video.captions.push(HtmlCaption {
+                        lang: caption.lang.clone(),
+                        label: caption.label.clone(),
+                        src: Self::import_src(backend, &game, &caption.uri, caption.remote)?,
+                    });
+                }
+
+                HtmlMedia::Video(video)
+            }
+            Media::Youtube(id) => HtmlMedia::Youtube(id.clone()),
+        })
+    }
+
+    pub fn html<S>(&self, rc: &RenderContext, rr: S) -> Result<String>
+    where
+        S: AsRef<str>,
+    {
+        match self {
+            HtmlMedia::Image(image) => image.figure_html(rr, rc.lang),
+            HtmlMedia::HBox(images) => {
+                let mut result = String::from(r#"<div class="hbox">"#);
+                for image in images.iter() {
+                    result.push_str(image.figure_html(rr.as_ref(), rc.lang)?.as_str());
+                }
+                result.push_str("</div>");
+                Ok(result)
+            }
+            HtmlMedia::Video(video) => video.html(rc, rr),
+            // Nothing is loaded from YouTube until the button is clicked
+            HtmlMedia::Youtube(id) => Ok(format!(
+                r#"<div class="youtube_facade" data-youtube="{}"><button class="youtube_load">{}{}</button><p>{}</p></div>"#,
+                xml::escape_str(id),
+                rc.icon("site", "youtube")?,
+                xml::escape_str(rc.ui("media.youtube_load")),
+                xml::escape_str(rc.ui("media.youtube_notice"))
+            )),
+        }
     }
 }
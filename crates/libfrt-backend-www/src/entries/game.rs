@@ -5,27 +5,53 @@ use serde::Serialize;
 
 use crate::BackendWWW;
 
-use super::common::{HtmlImage, HtmlText};
+use super::common::{HtmlImage, HtmlMedia, HtmlText};
 use libfrt::{
     entries::{
         game::{Description, Game},
+        link::{Link, LinkCategory, LocalizationPatch},
         Bundle,
     },
     i18n::LangId,
 };
 
+pub struct CookedLink {
+    pub label: String,
+    pub href: String,
+    pub icon: String,
+    pub patch: Option<LocalizationPatch>,
+    /// Localization patch for the language of the page
+    pub highlighted: bool,
+}
+
+pub struct CookedLinkGroup {
+    pub category: &'static str,
+    pub links: Vec<CookedLink>,
+}
+
 pub struct CookedGameL10n {
     pub name: HtmlText,
     pub description: HtmlText,
     pub brief_description: HtmlText,
+    /// Links available in this language, grouped by category
+    pub link_groups: Vec<CookedLinkGroup>,
+    /// Store links with distinct icons, for lists
+    pub stores: Vec<CookedLink>,
+}
+
+pub struct CookedMedia {
+    pub sensitive: bool,
+    pub media: HtmlMedia,
 }
 
 pub struct CookedGameNonl10n {
-    pub thumbnail: HtmlImage,
+    pub thumbnail: Option<HtmlImage>,
+    pub medias: Vec<CookedMedia>,
 }
 
 pub struct GameWWW {
     pub orig: Rc<Game>,
+    pub facets: Vec<String>,
     pub cooked: HashMap<LangId, CookedGameL10n>,
     pub cooked_nonl10n: CookedGameNonl10n,
 }
@@ -51,6 +77,57 @@ impl GameWWW {
         })
     }
 
+    fn cook_link(link: &Link, lang: LangId) -> CookedLink {
+        CookedLink {
+            label: link.localized_label(lang).to_owned(),
+            href: link.uri.clone(),
+            icon: match &link.rule {
+                Some(rule) => rule.icon.clone(),
+                None => String::from("misc-link"),
+            },
+            patch: link.patch.clone(),
+            highlighted: link.is_highlighted(lang),
+        }
+    }
+
+    fn cook_links(links: &[Link], lang: LangId) -> Vec<CookedLinkGroup> {
+        let mut groups: Vec<CookedLinkGroup> = Vec::new();
+
+        for link in Link::displayed(links, lang) {
+            let category = link.category().as_str();
+
+            match groups.last_mut() {
+                Some(group) if group.category == category => {
+                    group.links.push(Self::cook_link(link, lang))
+                }
+                _ => groups.push(CookedLinkGroup {
+                    category,
+                    links: vec![Self::cook_link(link, lang)],
+                }),
+            }
+        }
+
+        groups
+    }
+
+    fn cook_stores(links: &[Link], lang: LangId) -> Vec<CookedLink> {
+        let mut links: Vec<&Link> = links
+            .iter()
+            .filter(|l| l.category() == LinkCategory::Store && l.is_available(lang))
+            .collect();
+        links.sort_by_key(|l| l.weight());
+
+        let mut stores: Vec<CookedLink> = Vec::new();
+        for link in links {
+            let link = Self::cook_link(link, lang);
+            if !stores.iter().any(|s| s.icon == link.icon) {
+                stores.push(link);
+            }
+        }
+
+        stores
+    }
+
     pub fn cook_game(game: Rc<Game>, backend: &BackendWWW) -> Result<GameWWW> {
         let mut cooked = HashMap::new();
 
@@ -96,15 +173,32 @@ impl GameWWW {
                     .into(),
                     description: description,
                     brief_description: brief_description.into(),
+                    link_groups: Self::cook_links(&game.links, *lang),
+                    stores: Self::cook_stores(&game.links, *lang),
                 },
             );
         }
 
+        let mut medias = Vec::new();
+        for gm in game.medias.iter() {
+            medias.push(CookedMedia {
+                sensitive: gm.sensitive,
+                media: HtmlMedia::from_media(game.clone(), backend, &gm.media)?,
+            });
+        }
+
         Ok(GameWWW {
             orig: game.clone(),
+            facets: game.facets(),
             cooked,
             cooked_nonl10n: CookedGameNonl10n {
-                thumbnail: backend.import_image(&game.thumbnail, game.clone() as Rc<dyn Bundle>)?,
+                thumbnail: match &game.thumbnail {
+                    Some(thumbnail) => {
+                        Some(backend.import_image(thumbnail, game.clone() as Rc<dyn Bundle>)?)
+                    }
+                    None => None,
+                },
+                medias,
             },
         })
     }
@@ -16,11 +16,15 @@ use anyhow::Result;
 use entries::common::HtmlImage;
 use libfrt::entries::media::{Image, ImageSource};
 use libfrt::entries::Bundle;
+use pages::game::PageGame;
 use pages::list::PageList;
+use pages::searchdb::PageSearchDB;
+use pages::sitemap::PageSitemap;
 
 use crate::rc::RenderContext;
 use entries::game::GameWWW;
-use libfrt::backend::{Backend, BackendArguments};
+use libfrt::backend::registry::BackendRegistration;
+use libfrt::backend::{clean_output_dir, Backend, BackendArguments};
 use libfrt::i18n::LangId;
 use libfrt::profile::Profile;
 use libfrt::utils::fs::{copy_dir, ensure_dir};
@@ -29,6 +33,16 @@ use pages::{misc::PageMisc, Page, PageRenderOutput};
 use profile::ProfileWWW;
 use stylesheet::Stylesheets;
 
+/// Registration of the WWW backend
+pub fn registration() -> BackendRegistration {
+    BackendRegistration {
+        name: "www",
+        description: "Static website",
+        constructor: Some(|value| Ok(Box::new(BackendWWW::new(value)?))),
+        config: profile::CONFIG_KEYS,
+    }
+}
+
 enum OutputMode {
     NoOutput,
     Filesystem(PathBuf),
@@ -89,34 +103,76 @@ impl BackendWWW {
         backend
             .pages
             .insert("list".to_string(), Box::new(PageList::new()));
+        backend
+            .pages
+            .insert("game".to_string(), Box::new(PageGame::new()));
+        backend
+            .pages
+            .insert("sitemap".to_string(), Box::new(PageSitemap::new()));
+        backend
+            .pages
+            .insert("searchdb".to_string(), Box::new(PageSearchDB::new()));
 
         Ok(backend)
     }
 
+    /// Every stock link rule must use an icon in FGI-icons.json
+    fn check_link_icons(&self, data: &ContextData) -> Result<()> {
+        if self.profile.path_icon.is_empty() {
+            return Ok(());
+        }
+
+        let mut rules: Vec<_> = data.link_rules.rules.values().collect();
+        rules.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        let mut missing = 0;
+        for rule in rules {
+            if !self.icons.contains_key(&rule.icon) {
+                error!(
+                    "StockLink '{}': icon '{}' not found in FGI-icons.json",
+                    rule.name, rule.icon
+                );
+                missing += 1;
+            }
+        }
+
+        if missing > 0 {
+            libfrt::bail!(NotExist, "{} stock link rules with unknown icons", missing)
+        }
+
+        Ok(())
+    }
+
+    /// Copy a file of a bundle to the output assets, returns the path
+    /// relative to the site root.
+    pub fn import_file(&self, name: &str, bundle: Rc<dyn Bundle>) -> Result<String> {
+        let new_path =
+            Path::new(format!("assets/{}/{}/{}", bundle.kind(), { bundle.id() }, name).as_str())
+                .to_path_buf();
+
+        if let OutputMode::Filesystem(output_dir) = &self.output {
+            let target_file = output_dir.join(&new_path);
+
+            if std::fs::metadata(&target_file).is_err() {
+                ensure_dir(&target_file)?;
+                std::fs::copy(bundle.path().join(name), &target_file)?;
+            }
+        }
+
+        Ok(new_path.display().to_string())
+    }
+
     pub fn import_image(&self, image: &Image, bundle: Rc<dyn Bundle>) -> Result<HtmlImage> {
         let mut hi = HtmlImage::init_base_from_image(image);
 
         match &image.source {
             ImageSource::LocalShared(_) => todo!(),
             ImageSource::Bundled(s) => {
-                let new_path = Path::new(
-                    format!("assets/{}/{}/{}", bundle.kind(), { bundle.id() }, s).as_str(),
-                )
-                .to_path_buf();
-
-                if let OutputMode::Filesystem(output_dir) = &self.output {
-                    let dir = output_dir.join(&new_path);
-                    let target_file = dir.join(s);
-
-                    if std::fs::metadata(&target_file).is_err() {
-                        ensure_dir(&dir)?;
-                        std::fs::copy(bundle.path().join(s), &dir)?;
-                    }
-                }
+                let new_path = self.import_file(s, bundle)?;
 
                 // TODO: webp convert support
 
-                hi.add_source_simple(new_path.display().to_string(), false, None)?;
+                hi.add_source_simple(new_path, false, None)?;
             }
             ImageSource::Remote(s) => {
                 hi.add_source_simple(s.to_owned(), true, None)?;
@@ -136,24 +192,15 @@ impl Backend for BackendWWW {
     ) -> Result<()> {
         info!("Re-syncing backend data");
 
-        if args.get_bool("fs_output") {
-            let output_dir = args
-                .get_string("output")
-                .ok_or_else(|| libfrt::err!(InvalidArgument, "Missing argument 'output'"))?;
-
-            self.output = OutputMode::Filesystem(Path::new(&output_dir).into());
-
-            if std::fs::metadata(&output_dir).is_ok() {
-                std::fs::remove_dir_all(&output_dir)?;
-            }
+        if let Some(output_dir) = clean_output_dir(args)? {
+            self.output = OutputMode::Filesystem(output_dir);
         }
 
         self.target = args.get_string("target").unwrap_or(String::new());
 
-        self.langs = data.ui.keys().map(|i| i.to_owned()).collect();
-        if !data.ui.contains_key(&LangId::default()) {
-            self.langs.push(LangId::default());
-        }
+        self.check_link_icons(data)?;
+
+        self.langs = data.langs();
 
         for game in data.games.values_mut() {
             //if game.dirty {
@@ -166,11 +213,12 @@ impl Backend for BackendWWW {
         Ok(())
     }
 
-    fn render(&self, _profile: &Profile, data: &ContextData) -> Result<BackendArguments> {
+    fn render(&self, profile: &Profile, data: &ContextData) -> Result<BackendArguments> {
         let mut ret = BackendArguments::default();
 
         let mut render_context = RenderContext {
             backend: &self,
+            profile,
             data: data,
             lang: LangId::default(),
         };
@@ -0,0 +1,168 @@
+use anyhow::Result;
+
+use super::template;
+use super::Page;
+use super::PageRenderOutput;
+use super::RenderContext;
+use super::CVMeta;
+use crate::entries::game::GameWWW;
+use crate::utils::xml;
+use askama::Template;
+use libfrt::entries::game::GameState;
+
+struct RelatedGame {
+    id: String,
+    name: String,
+}
+
+struct RelatedGroup {
+    kind: &'static str,
+    games: Vec<RelatedGame>,
+}
+
+struct GameTemplatePriv<'a> {
+    game: &'a GameWWW,
+    warn: Option<String>,
+    related: Vec<RelatedGroup>,
+}
+
+#[derive(Default)]
+struct TombstoneTemplatePriv {
+    name: String,
+    reason: Option<String>,
+}
+
+#[derive(Default)]
+struct RedirectTemplatePriv {
+    notice: String,
+}
+
+template!("game.html", GameTemplate, "../..", GameTemplatePriv<'a>, nodefault);
+template!("game-tombstone.html", TombstoneTemplate, "../..", TombstoneTemplatePriv);
+template!("game-redirect.html", RedirectTemplate, "../..", RedirectTemplatePriv);
+
+pub struct PageGame {}
+
+impl PageGame {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn related_games(rc: &RenderContext, game: &GameWWW) -> Result<Vec<RelatedGroup>> {
+        let mut groups: Vec<RelatedGroup> = Vec::new();
+
+        // relations are sorted by kind
+        for relation in game.orig.relations.iter() {
+            // target may be left out, e.g. by exclude_sensitive
+            let Some(target) = rc.backend.games.get(&relation.target) else {
+                continue;
+            };
+
+            let related = RelatedGame {
+                id: target.orig.id.clone(),
+                name: target.loc_view(rc.lang)?.loc.name.plain.clone(),
+            };
+
+            match groups.last_mut() {
+                Some(group) if group.kind == relation.kind.as_str() => group.games.push(related),
+                _ => groups.push(RelatedGroup {
+                    kind: relation.kind.as_str(),
+                    games: vec![related],
+                }),
+            }
+        }
+
+        Ok(groups)
+    }
+
+    fn render_game(&self, rc: &RenderContext, game: &GameWWW) -> Result<String> {
+        let view = game.loc_view(rc.lang)?;
+
+        let warn = match &game.orig.state {
+            GameState::Delisted { reason } => Some(match reason {
+                Some(reason) => format!(
+                    "{}<br>{}",
+                    rc.ui("warnbox.delisted"),
+                    xml::escape_str(reason)
+                ),
+                None => rc.ui("warnbox.delisted").to_owned(),
+            }),
+            _ => None,
+        };
+
+        let related = Self::related_games(rc, game)?;
+
+        let mut template = GameTemplate::new(rc, GameTemplatePriv { game, warn, related });
+
+        template.g.meta = Some(CVMeta {
+            title: view.loc.name.plain.clone(),
+            keywords: rc.ui("seo.base_keywords").to_owned(),
+            description: view.loc.brief_description.plain.clone(),
+            image: String::new(),
+        });
+        template.g.rating = game
+            .orig
+            .is_sensitive()
+            .then(|| game.orig.rating.as_str());
+
+        Ok(template.render()?)
+    }
+
+    fn render_tombstone(
+        &self,
+        rc: &RenderContext,
+        game: &GameWWW,
+        reason: &Option<String>,
+    ) -> Result<String> {
+        let mut template = TombstoneTemplate::new(rc);
+
+        template.c.name = game.loc_view(rc.lang)?.loc.name.plain.clone();
+        template.c.reason = reason.clone();
+        template.g.noindex = true;
+
+        Ok(template.render()?)
+    }
+
+    fn render_redirect(&self, rc: &RenderContext, target: &str) -> Result<String> {
+        let target = rc.backend.games.get(target).ok_or_else(|| {
+            libfrt::err!(NotExist, "Redirect target '{}' not found", target)
+        })?;
+
+        let href = format!("{}.html", target.orig.id);
+        let name = &target.loc_view(rc.lang)?.loc.name.html;
+
+        let mut template = RedirectTemplate::new(rc);
+
+        template.c.notice = rc
+            .ui("noticebox.replacedby")
+            .replacen("%s", &href, 1)
+            .replacen("%s", name, 1);
+        template.g.noindex = true;
+        template.g.redirect = Some(href);
+
+        Ok(template.render()?)
+    }
+}
+
+impl Page for PageGame {
+    fn render(&self, rc: &RenderContext) -> Result<PageRenderOutput> {
+        info!("Rendering: games");
+
+        let mut ret = PageRenderOutput::default();
+
+        for game in rc.backend.games.values() {
+            let content = match &game.orig.state {
+                GameState::Active | GameState::Delisted { .. } => self.render_game(rc, game)?,
+                GameState::Expunged { reason } => self.render_tombstone(rc, game, reason)?,
+                GameState::Redirected { target } => self.render_redirect(rc, target)?,
+            };
+
+            ret.extend(PageRenderOutput::single_page(
+                format!("{}/games/{}.html", rc.lang.as_str(), game.orig.id),
+                content,
+            ));
+        }
+
+        Ok(ret)
+    }
+}
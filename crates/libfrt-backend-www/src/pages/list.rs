@@ -5,11 +5,24 @@ use super::Page;
 use super::PageRenderOutput;
 use super::RenderContext;
 use crate::entries::game::GameWWW;
+use crate::profile::{ListOrder, ListProfile};
 use askama::Template;
+use libfrt::utils::collate::CollationKey;
+
+struct ListChainItem {
+    label: String,
+    href: String,
+    actived: bool,
+}
 
 #[derive(Default)]
 struct ListTemplatePriv<'a> {
     games: Vec<&'a GameWWW>,
+    chain: Vec<ListChainItem>,
+    page: usize,
+    pages: usize,
+    prev: Option<String>,
+    next: Option<String>,
 }
 
 template!("list.html", ListTemplate, "..", ListTemplatePriv<'a>);
@@ -21,22 +34,118 @@ impl PageList {
         Self {}
     }
 
-    fn render_list<F>(
+    fn label(rc: &RenderContext, list: &ListProfile) -> String {
+        let label = rc.ui(format!("list.by_{}", list.order.as_str()));
+
+        match list.facet.as_ref().and_then(|f| f.split_once(':')) {
+            Some((namespace, value)) => {
+                format!("{} · {}", rc.facet_label(namespace, value), label)
+            }
+            None => label.to_owned(),
+        }
+    }
+
+    fn sorted_games<'a>(rc: &'a RenderContext, list: &ListProfile) -> Result<Vec<&'a GameWWW>> {
+        let mut games: Vec<_> = rc
+            .backend
+            .games
+            .values()
+            .filter(|g| g.orig.state.is_listed())
+            .filter(|g| match &list.facet {
+                Some(facet) => g.facets.contains(facet),
+                None => true,
+            })
+            .collect();
+
+        match list.order {
+            ListOrder::Id => games.sort_unstable_by(|a, b| a.orig.id.cmp(&b.orig.id)),
+            ListOrder::Name => {
+                let mut keyed = games
+                    .into_iter()
+                    .map(|g| {
+                        let name = &g.loc_view(rc.lang)?.loc.name.plain;
+                        Ok((CollationKey::new(name, rc.lang), g))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                keyed.sort_unstable_by(|a, b| {
+                    a.0.cmp(&b.0).then_with(|| a.1.orig.id.cmp(&b.1.orig.id))
+                });
+
+                games = keyed.into_iter().map(|(_, g)| g).collect();
+            }
+            ListOrder::Added => games.sort_unstable_by(|a, b| {
+                b.orig
+                    .added
+                    .cmp(&a.orig.added)
+                    .then_with(|| a.orig.id.cmp(&b.orig.id))
+            }),
+            ListOrder::Updated => games.sort_unstable_by(|a, b| {
+                b.orig
+                    .updated
+                    .cmp(&a.orig.updated)
+                    .then_with(|| a.orig.id.cmp(&b.orig.id))
+            }),
+        }
+
+        Ok(games)
+    }
+
+    fn render_list(
         &self,
-        template: &mut ListTemplate,
-        compare: F,
-        fn_suffix: &str,
-    ) -> Result<PageRenderOutput>
-    where
-        F: FnMut(&&GameWWW, &&GameWWW) -> std::cmp::Ordering,
-    {
-        template.c.games = template.rc.backend.games.values().collect();
-        template.c.games.sort_unstable_by(compare);
-
-        Ok(PageRenderOutput::single_page(
-            format!("{}/list{}.html", template.rc.lang.as_str(), fn_suffix),
-            template.render()?,
-        ))
+        rc: &RenderContext,
+        lists: &[ListProfile],
+        index: usize,
+    ) -> Result<PageRenderOutput> {
+        let list = &lists[index];
+        let games = Self::sorted_games(rc, list)?;
+
+        let chunk_size = match list.page_size {
+            0 => games.len().max(1),
+            n => n,
+        };
+        let chunks: Vec<_> = games.chunks(chunk_size).collect();
+        let pages = chunks.len().max(1);
+
+        let mut ret = PageRenderOutput::default();
+
+        for page in 1..=pages {
+            let mut template = ListTemplate::new(rc);
+
+            template.c.games = chunks.get(page - 1).map(|c| c.to_vec()).unwrap_or_default();
+            template.c.chain = lists
+                .iter()
+                .enumerate()
+                .map(|(i, l)| ListChainItem {
+                    label: Self::label(rc, l),
+                    href: l.file_name(i, 1),
+                    actived: i == index,
+                })
+                .collect();
+            template.g.rating = template
+                .c
+                .games
+                .iter()
+                .map(|g| g.orig.rating)
+                .max()
+                .filter(|r| r.is_sensitive())
+                .map(|r| r.as_str());
+            template.c.page = page;
+            template.c.pages = pages;
+            template.c.prev = (page > 1).then(|| list.file_name(index, page - 1));
+            template.c.next = (page < pages).then(|| list.file_name(index, page + 1));
+
+            ret.extend(PageRenderOutput::single_page(
+                format!(
+                    "{}/{}",
+                    rc.lang.as_str(),
+                    list.file_name(index, page)
+                ),
+                template.render()?,
+            ));
+        }
+
+        Ok(ret)
     }
 }
 
@@ -44,12 +153,118 @@ impl Page for PageList {
     fn render(&self, rc: &RenderContext) -> Result<PageRenderOutput> {
         info!("Rendering: list");
 
-        let mut template = ListTemplate::new(rc);
+        let lists = &rc.backend.profile.lists;
 
         let mut ret = PageRenderOutput::default();
 
-        ret.extend(self.render_list(&mut template, |a, b| a.orig.id.cmp(&b.orig.id), "")?);
+        for index in 0..lists.len() {
+            ret.extend(self.render_list(rc, lists, index)?);
+        }
 
         Ok(ret)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use libfrt::backend::{Backend, BackendArguments};
+    use libfrt::i18n::LangId;
+    use libfrt::profile::Profile;
+    use libfrt::testing::Fixture;
+
+    use super::PageList;
+    use crate::pages::{File, Page};
+    use crate::rc::RenderContext;
+    use crate::BackendWWW;
+
+    /// English pages of the list variants configured by `lists`, by path
+    fn render(lists: &str) -> BTreeMap<String, String> {
+        let mut fixture = Fixture::new("www-list");
+
+        let icons = fixture.root.join("icons");
+        std::fs::create_dir_all(&icons).unwrap();
+        std::fs::write(icons.join("icon.css"), "").unwrap();
+        let json = r#"{"": 1, "site-steam": 2, "misc-fallback": 3}"#;
+        std::fs::write(icons.join("FGI-icons.json"), json).unwrap();
+
+        let demo = concat!(env!("CARGO_MANIFEST_DIR"), "/../../demo/www");
+        let config = format!(
+            "path_static_layers = ['{demo}/root']\n\
+             path_stylesheets = ['{demo}/styles']\n\
+             path_icon = ['{}']\n{lists}",
+            icons.display()
+        );
+        let mut backend = BackendWWW::new(Some(toml::from_str(&config).unwrap())).unwrap();
+
+        let profile = Profile::default();
+        let args = BackendArguments::default();
+        backend.resync(&profile, &mut fixture.data, &args).unwrap();
+
+        let rc = RenderContext {
+            backend: &backend,
+            profile: &profile,
+            data: &fixture.data,
+            lang: LangId::default(),
+        };
+        let output = PageList::new().render(&rc).unwrap();
+
+        output
+            .pages
+            .into_iter()
+            .map(|(path, file)| match file {
+                File::Regular(contents) => (path, contents),
+                _ => panic!("{path} is not a regular file"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_paging() {
+        let pages = render(
+            "[[lists]]\norder = 'id'\n\
+             [[lists]]\norder = 'name'\npage_size = 2\n\
+             [[lists]]\norder = 'id'\npage_size = 2\nfacet = 'platform:linux'\n",
+        );
+
+        let paths: Vec<_> = pages.keys().map(|p| p.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "en/list-by-name-2.html",
+                "en/list-by-name.html",
+                "en/list-platform-linux.html",
+                "en/list.html",
+            ]
+        );
+
+        let game = |id: &str| format!("href=\"../en/games/{id}.html\"");
+        let listed = |path: &str| -> Vec<&str> {
+            ["adult-game", "bar-game", "foo-game"]
+                .into_iter()
+                .filter(|id| pages[path].contains(&game(id)))
+                .collect()
+        };
+
+        assert_eq!(
+            listed("en/list.html"),
+            ["adult-game", "bar-game", "foo-game"]
+        );
+        assert!(!pages["en/list.html"].contains("class=\"pagination\""));
+
+        let first = &pages["en/list-by-name.html"];
+        assert_eq!(listed("en/list-by-name.html"), ["adult-game", "bar-game"]);
+        assert!(first.contains("<span>1 / 2</span>"));
+        assert!(first.contains("rel=\"next\" href=\"../en/list-by-name-2.html\""));
+        assert!(!first.contains("rel=\"prev\""));
+
+        let second = &pages["en/list-by-name-2.html"];
+        assert_eq!(listed("en/list-by-name-2.html"), ["foo-game"]);
+        assert!(second.contains("rel=\"prev\" href=\"../en/list-by-name.html\""));
+        assert!(!second.contains("rel=\"next\""));
+        assert!(second.contains("href=\"../en/list-platform-linux.html\""));
+
+        assert_eq!(listed("en/list-platform-linux.html"), ["foo-game"]);
+    }
+}
@@ -1,5 +1,8 @@
+pub mod game;
 pub mod misc;
 pub mod list;
+pub mod searchdb;
+pub mod sitemap;
 
 use std::collections::HashMap;
 
@@ -45,11 +48,13 @@ pub struct CVMeta {
 pub struct TemplateCommonVariables {
     meta: Option<CVMeta>,
     noindex: bool,
+    rating: Option<&'static str>,
+    redirect: Option<String>,
     extra_styles: Option<String>,
 }
 
 macro_rules! template {
-    ($file:literal, $name:ident, $rr:literal $(, $custom_field:ty)?) => {
+    (@struct $file:literal, $name:ident $(, $custom_field:ty)?) => {
         #[derive(Template)]
         #[template(path = $file)]
         pub(crate) struct $name<'a> {
@@ -58,6 +63,23 @@ macro_rules! template {
             pub g: $crate::pages::TemplateCommonVariables,
             $(c: $custom_field,)?
         }
+    };
+    ($file:literal, $name:ident, $rr:literal, $custom_field:ty, nodefault) => {
+        template!(@struct $file, $name, $custom_field);
+
+        impl<'a> $name<'a> {
+            fn new(rc: &'a RenderContext, c: $custom_field) -> $name<'a> {
+                Self {
+                    rr: $rr,
+                    rc,
+                    g: $crate::pages::TemplateCommonVariables::default(),
+                    c,
+                }
+            }
+        }
+    };
+    ($file:literal, $name:ident, $rr:literal $(, $custom_field:ty)?) => {
+        template!(@struct $file, $name $(, $custom_field)?);
 
         impl<'a> $name<'a> {
             fn new(rc: &'a RenderContext) -> $name<'a> {
@@ -69,7 +91,7 @@ macro_rules! template {
                 }
             }
         }
-    }
+    };
 }
 
 use template;
\ No newline at end of file
@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::Page;
+use super::PageRenderOutput;
+use super::RenderContext;
+use libfrt::i18n::LangId;
+
+#[derive(Serialize, Default)]
+struct SearchDB<'a> {
+    /// Facet to ids of games with that facet
+    facets: BTreeMap<&'a str, Vec<&'a str>>,
+    /// Game id to last modified timestamp, for ordering search result
+    lastmod: BTreeMap<&'a str, i64>,
+}
+
+pub struct PageSearchDB {}
+
+impl PageSearchDB {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Page for PageSearchDB {
+    fn render(&self, rc: &RenderContext) -> Result<PageRenderOutput> {
+        if rc.lang != LangId::default() {
+            return Ok(PageRenderOutput::default());
+        }
+
+        info!("Rendering: searchdb");
+
+        let mut db = SearchDB::default();
+
+        for game in rc.backend.games.values() {
+            if !game.orig.state.is_listed() {
+                continue;
+            }
+
+            for facet in game.facets.iter() {
+                db.facets
+                    .entry(facet.as_str())
+                    .or_default()
+                    .push(game.orig.id.as_str());
+            }
+
+            db.lastmod
+                .insert(game.orig.id.as_str(), game.orig.updated.unwrap_or(0));
+        }
+
+        for ids in db.facets.values_mut() {
+            ids.sort_unstable();
+        }
+
+        Ok(PageRenderOutput::single_page(
+            "scripts/searchdb.json".to_owned(),
+            serde_json::to_string(&db)?,
+        ))
+    }
+}
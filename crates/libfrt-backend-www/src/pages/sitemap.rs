@@ -0,0 +1,70 @@
+use anyhow::Result;
+
+use super::Page;
+use super::PageRenderOutput;
+use super::RenderContext;
+use crate::utils::xml;
+use libfrt::entries::game::GameState;
+use libfrt::i18n::LangId;
+
+pub struct PageSitemap {}
+
+impl PageSitemap {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Page for PageSitemap {
+    fn render(&self, rc: &RenderContext) -> Result<PageRenderOutput> {
+        if rc.lang != LangId::default() {
+            return Ok(PageRenderOutput::default());
+        }
+
+        let prefix = rc.profile.authority_prefix.trim_end_matches('/');
+        if prefix.is_empty() {
+            info!("No authority prefix provided, skip rendering sitemap");
+            return Ok(PageRenderOutput::default());
+        }
+
+        info!("Rendering: sitemap");
+
+        let mut locs = Vec::new();
+
+        let mut games: Vec<_> = rc
+            .backend
+            .games
+            .values()
+            .filter(|g| match g.orig.state {
+                GameState::Active | GameState::Delisted { .. } => true,
+                GameState::Expunged { .. } | GameState::Redirected { .. } => false,
+            })
+            .map(|g| g.orig.id.as_str())
+            .collect();
+        games.sort_unstable();
+
+        for lang in rc.backend.langs.iter() {
+            locs.push(format!("{}/{}/index.html", prefix, lang));
+            locs.push(format!("{}/{}/list.html", prefix, lang));
+
+            for id in games.iter() {
+                locs.push(format!("{}/{}/games/{}.html", prefix, lang, id));
+            }
+        }
+
+        let mut s = String::from(concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            "\n",
+            r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#,
+            "\n"
+        ));
+
+        for loc in locs.iter() {
+            s.push_str(format!("\t<url><loc>{}</loc></url>\n", xml::escape_str(loc)).as_str());
+        }
+
+        s.push_str("</urlset>\n");
+
+        Ok(PageRenderOutput::single_page("sitemap2.xml".to_owned(), s))
+    }
+}
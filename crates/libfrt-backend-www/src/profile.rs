@@ -1,5 +1,122 @@
+use std::collections::HashMap;
+
 use serde::Deserialize;
 use anyhow::Result;
+use libfrt::backend::registry::BackendConfigKey;
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ListOrder {
+    Id,
+    Name,
+    Added,
+    Updated,
+}
+
+impl ListOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListOrder::Id => "id",
+            ListOrder::Name => "name",
+            ListOrder::Added => "added",
+            ListOrder::Updated => "updated",
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ListProfile {
+    pub order: ListOrder,
+
+    /// Games per page, 0 to put all games on a single page
+    #[serde(default)]
+    pub page_size: usize,
+
+    /// Only list games with this facet, such as `platform:linux`
+    #[serde(default)]
+    pub facet: Option<String>,
+}
+
+impl ListProfile {
+    /// Output file name (without language prefix) of a page of the list
+    /// variant at `index`. The first list variant is the default one and is
+    /// rendered as `list.html`.
+    pub fn file_name(&self, index: usize, page: usize) -> String {
+        let mut base = String::from("list");
+
+        if index != 0 {
+            if let Some(facet) = &self.facet {
+                base.push('-');
+                base.push_str(facet.replace(':', "-").as_str());
+            }
+
+            if self.order != ListOrder::Id || self.facet.is_none() {
+                base.push_str("-by-");
+                base.push_str(self.order.as_str());
+            }
+        }
+
+        match page {
+            0 | 1 => format!("{}.html", base),
+            _ => format!("{}-{}.html", base, page),
+        }
+    }
+}
+
+/// How sensitive medias and thumbnails are presented before click-through
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum SensitiveMode {
+    Show,
+    Blur,
+    Hide,
+}
+
+/// Keys of `ProfileWWW`, for `frt backends`
+pub const CONFIG_KEYS: &[BackendConfigKey] = &[
+    BackendConfigKey {
+        key: "path_templates",
+        kind: "list of globs",
+        default: r#"["www/templates/**/*"]"#,
+        description: "Page templates",
+    },
+    BackendConfigKey {
+        key: "path_static_layers",
+        kind: "list of paths",
+        default: r#"["www/root"]"#,
+        description: "Directories copied to the output as is, in order",
+    },
+    BackendConfigKey {
+        key: "path_stylesheets",
+        kind: "list of paths",
+        default: r#"["www/styles"]"#,
+        description: "Stylesheet sources",
+    },
+    BackendConfigKey {
+        key: "path_icon",
+        kind: "list of paths",
+        default: r#"["www/icons/build"]"#,
+        description: "Icon font builds with FGI-icons.json",
+    },
+    BackendConfigKey {
+        key: "image_convert_webp",
+        kind: "bool",
+        default: "true",
+        description: "Convert bundled images to WebP",
+    },
+    BackendConfigKey {
+        key: "sensitive_media",
+        kind: "show | blur | hide",
+        default: r#""blur""#,
+        description: "How sensitive medias are presented before click-through, hidden ones are only loaded on click",
+    },
+    BackendConfigKey {
+        key: "lists",
+        kind: "list of tables",
+        default: "by id, name, added and updated",
+        description: "List variants with order, page_size and facet",
+    },
+];
 
 #[derive(Deserialize, Clone, Debug)]
 #[serde(default)]
@@ -10,6 +127,11 @@ pub struct ProfileWWW {
     pub path_icon: Vec<String>,
 
     pub image_convert_webp: bool,
+
+    pub sensitive_media: SensitiveMode,
+
+    /// List variants, the first one is rendered as list.html
+    pub lists: Vec<ListProfile>,
 }
 
 impl Default for ProfileWWW {
@@ -21,12 +143,106 @@ impl Default for ProfileWWW {
             path_icon: vec![String::from("www/icons/build")],
 
             image_convert_webp: true,
+
+            sensitive_media: SensitiveMode::Blur,
+
+            lists: vec![
+                ListProfile { order: ListOrder::Id, page_size: 0, facet: None },
+                ListProfile { order: ListOrder::Name, page_size: 50, facet: None },
+                ListProfile { order: ListOrder::Added, page_size: 50, facet: None },
+                ListProfile { order: ListOrder::Updated, page_size: 50, facet: None },
+            ],
         }
     }
 }
 
 impl ProfileWWW {
     pub fn from_value(value: toml::Value) -> Result<Self> {
-        Ok(ProfileWWW::deserialize(value)?)
+        let profile = ProfileWWW::deserialize(value)?;
+        profile.check_lists()?;
+        Ok(profile)
     }
-}
\ No newline at end of file
+
+    /// Reject list variants which would overwrite the pages of each other
+    fn check_lists(&self) -> Result<()> {
+        let mut names = HashMap::new();
+
+        for (index, list) in self.lists.iter().enumerate() {
+            let name = list.file_name(index, 1);
+            if let Some(other) = names.insert(name.clone(), index) {
+                libfrt::bail!(
+                    InvalidArgument,
+                    "List variants #{} and #{} are both rendered as '{}'",
+                    other + 1,
+                    index + 1,
+                    name
+                )
+            }
+        }
+
+        Ok(())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::ProfileWWW;
+
+    fn profile(toml: &str) -> anyhow::Result<ProfileWWW> {
+        ProfileWWW::from_value(toml::from_str(toml)?)
+    }
+
+    #[test]
+    fn test_list_file_name() {
+        let lists = profile(
+            "[[lists]]\norder = 'name'\n\
+             [[lists]]\norder = 'id'\n\
+             [[lists]]\norder = 'id'\nfacet = 'platform:linux'\n\
+             [[lists]]\norder = 'added'\nfacet = 'platform:linux'\n",
+        )
+        .unwrap()
+        .lists;
+
+        let names: Vec<_> = lists
+            .iter()
+            .enumerate()
+            .map(|(i, l)| l.file_name(i, 1))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "list.html",
+                "list-by-id.html",
+                "list-platform-linux.html",
+                "list-platform-linux-by-added.html",
+            ]
+        );
+        assert_eq!(lists[0].file_name(0, 2), "list-2.html");
+        assert_eq!(
+            lists[3].file_name(3, 10),
+            "list-platform-linux-by-added-10.html"
+        );
+    }
+
+    #[test]
+    fn test_list_duplicates() {
+        assert!(profile("").is_ok());
+
+        let e = profile(
+            "[[lists]]\norder = 'id'\n\
+             [[lists]]\norder = 'name'\npage_size = 10\n\
+             [[lists]]\norder = 'name'\npage_size = 50\n",
+        )
+        .unwrap_err();
+        assert!(e
+            .to_string()
+            .contains("List variants #2 and #3 are both rendered as 'list-by-name.html'"));
+
+        // facets are named without namespace separator
+        let e = profile(
+            "[[lists]]\norder = 'id'\n\
+             [[lists]]\norder = 'id'\nfacet = 'a:b-c'\n\
+             [[lists]]\norder = 'id'\nfacet = 'a-b:c'\n",
+        );
+        assert!(e.is_err());
+    }
+}
@@ -1,60 +1,28 @@
+use std::borrow::Borrow;
 use std::path::Path;
 
 use anyhow::Result;
 
+use crate::profile::SensitiveMode;
 use crate::BackendWWW;
-use libfrt::{i18n::LangId, utils::fs::get_mtime, ContextData};
+use libfrt::{i18n::LangId, profile::Profile, utils::fs::get_mtime, ContextData};
 
 pub struct RenderContext<'a> {
     pub backend: &'a BackendWWW,
 
+    pub profile: &'a Profile,
+
     pub data: &'a ContextData,
     pub lang: LangId,
 }
 
 impl RenderContext<'_> {
     pub fn ui_raw(&self, k: impl AsRef<str>) -> Option<&toml::Value> {
-        let k = k.as_ref().split('.');
-        let mut v = self.data.ui.get(&self.lang);
-
-        for i in k.into_iter() {
-            match v {
-                Some(vv) => match vv {
-                    toml::Value::Table(t) => {
-                        v = t.get(i);
-                    }
-                    _ => {
-                        v = None;
-                        break;
-                    }
-                },
-                None => break,
-            }
-        }
-
-        v
+        self.data.ui_raw(self.lang, k.as_ref())
     }
 
     pub fn ui(&self, k: impl AsRef<str>) -> &str {
-        match self.ui_raw(k.as_ref()) {
-            Some(v) => match v {
-                toml::Value::String(s) => s.as_str(),
-                _ => {
-                    warn!(
-                        "'{}' ui config is not a valid string type, fallback to empty string",
-                        k.as_ref()
-                    );
-                    ""
-                }
-            },
-            None => {
-                warn!(
-                    "'{}' was not found in ui config, fallback to empty string",
-                    k.as_ref()
-                );
-                ""
-            }
-        }
+        self.data.ui(self.lang, k.as_ref())
     }
 
     pub fn icon(&self, scope: impl AsRef<str>, name: impl AsRef<str>) -> Result<String> {
@@ -86,6 +54,40 @@ impl RenderContext<'_> {
         ))
     }
 
+    /// Localized label of a facet value, fallback to the value itself
+    pub fn facet_label(&self, namespace: impl AsRef<str>, value: impl AsRef<str>) -> String {
+        self.data.facet_label(self.lang, namespace.as_ref(), value.as_ref())
+    }
+
+    /// Same as `icon()`, but takes a full icon id such as `site-steam`
+    pub fn icon_by_id(&self, id: impl AsRef<str>) -> Result<String> {
+        match id.as_ref().split_once('-') {
+            Some((scope, name)) => self.icon(scope, name),
+            None => self.icon("misc", id),
+        }
+    }
+
+    /// Wrap rendered media html with a click-through if it is sensitive.
+    /// Hidden medias are kept in a `<template>`, so nothing is loaded
+    /// before the click.
+    pub fn sensitive(&self, html: impl AsRef<str>, sensitive: impl Borrow<bool>) -> String {
+        let html = html.as_ref();
+        let (class, html) = match (*sensitive.borrow(), self.backend.profile.sensitive_media) {
+            (false, _) | (true, SensitiveMode::Show) => return html.to_owned(),
+            (true, SensitiveMode::Blur) => ("sensitive blur", html.to_owned()),
+            (true, SensitiveMode::Hide) => {
+                ("sensitive hide", format!("<template>{}</template>", html))
+            }
+        };
+
+        format!(
+            r#"<div class="{}">{}<button class="sensitive_reveal" type="button">{}</button></div>"#,
+            class,
+            html,
+            self.ui("sensitive.reveal")
+        )
+    }
+
     fn find_local_file(
         &self,
         dir: &Vec<String>,
@@ -1,11 +1,15 @@
+pub mod registry;
+
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 
 use crate::ContextData;
 use crate::profile::Profile;
+use crate::utils::fs::ensure_dir;
 
-#[derive(Default)]
+#[derive(Default, Clone, Debug)]
 pub struct BackendArguments(HashMap<String, serde_json::Value>);
 
 impl BackendArguments {
@@ -44,6 +48,59 @@ impl BackendArguments {
     }
 }
 
+/// Output directory from the `fs_output` and `output` arguments, `None`
+/// without file-system output
+pub fn output_dir(args: &BackendArguments) -> Result<Option<PathBuf>> {
+    if !args.get_bool("fs_output") {
+        return Ok(None);
+    }
+
+    let output_dir = args
+        .get_string("output")
+        .ok_or_else(|| crate::err!(InvalidArgument, "Missing argument 'output'"))?;
+
+    Ok(Some(output_dir.into()))
+}
+
+/// Same as `output_dir`, with the directory removed for a fresh build
+pub fn clean_output_dir(args: &BackendArguments) -> Result<Option<PathBuf>> {
+    let output_dir = output_dir(args)?;
+
+    if let Some(dir) = &output_dir {
+        if std::fs::metadata(dir).is_ok() {
+            std::fs::remove_dir_all(dir)?;
+        }
+    }
+
+    Ok(output_dir)
+}
+
+/// Copy the bundle files used by games to the `assets/` paths of the WWW
+/// backend under `dir`. Returns the copied paths, relative to `dir`.
+pub fn copy_bundled_files(data: &ContextData, dir: &Path) -> Result<Vec<String>> {
+    let mut copied = Vec::new();
+
+    for game in data.games.values() {
+        for name in game.bundled_files() {
+            let path = game.asset_path(name);
+            let target = dir.join(&path);
+            ensure_dir(&target)?;
+            std::fs::copy(game.bundle_path.join(name), &target)?;
+            copied.push(path);
+        }
+    }
+
+    Ok(copied)
+}
+
+/// Relative path from a file `depth` directories deep to the output root
+pub fn relative_root(depth: usize) -> String {
+    match depth {
+        0 => String::from("."),
+        _ => vec![".."; depth].join("/"),
+    }
+}
+
 pub trait Backend {
     fn resync(
         &mut self,
@@ -60,4 +117,11 @@ pub trait Backend {
         data: &ContextData
     ) -> Result<BackendArguments>;
 
-}
\ No newline at end of file
+}
+
+/// A backend selected for a build, with arguments of its own
+pub struct BackendInstance {
+    pub name: String,
+    pub backend: Box<dyn Backend>,
+    pub args: BackendArguments,
+}
@@ -0,0 +1,347 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use super::Backend;
+use crate::profile::Profile;
+
+/// Creates a backend from its `[backends.<name>]` table, if any
+pub type BackendConstructor = fn(Option<toml::Value>) -> Result<Box<dyn Backend>>;
+
+/// A key of the `[backends.<name>]` table
+pub struct BackendConfigKey {
+    pub key: &'static str,
+    /// Type of the value, e.g. `bool` or `list of strings`
+    pub kind: &'static str,
+    pub default: &'static str,
+    pub description: &'static str,
+}
+
+/// The `include_unlisted` key of backends exporting the catalog as data,
+/// see `ContextData::exported_games`
+pub const INCLUDE_UNLISTED: BackendConfigKey = BackendConfigKey {
+    key: "include_unlisted",
+    kind: "bool",
+    default: "true",
+    description: "Also write delisted games and tombstones",
+};
+
+/// A compiled-in backend which can be selected by name
+pub struct BackendRegistration {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// `None` for backends which only load and validate sources
+    pub constructor: Option<BackendConstructor>,
+    pub config: &'static [BackendConfigKey],
+}
+
+impl BackendRegistration {
+    /// The `null` backend, which renders nothing
+    pub fn null() -> Self {
+        Self {
+            name: "null",
+            description: "Only load and validate sources, print statistics",
+            constructor: None,
+            config: &[],
+        }
+    }
+}
+
+impl std::fmt::Display for BackendRegistration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}: {}", self.name, self.description)?;
+        for key in self.config.iter() {
+            writeln!(
+                f,
+                "    {} ({}, default: {})",
+                key.key, key.kind, key.default
+            )?;
+            writeln!(f, "        {}", key.description)?;
+        }
+        Ok(())
+    }
+}
+
+/// A backend selected by name, with its configuration from the profile
+pub struct ResolvedBackend<'r> {
+    /// Name it was selected by, the key of its `[backends.<name>]` table
+    pub name: String,
+    pub registration: &'r BackendRegistration,
+    /// The `[backends.<name>]` table without the `backend` key
+    pub config: Option<toml::Value>,
+}
+
+impl ResolvedBackend<'_> {
+    /// Create the backend, `None` for validation-only backends
+    pub fn create(self) -> Result<Option<Box<dyn Backend>>> {
+        match self.registration.constructor {
+            Some(constructor) => Ok(Some(constructor(self.config)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Backends known to the tool.
+///
+/// A backend is selected either by its registered name, or by the key of a
+/// `[backends.<key>]` table in the profile which names the registered
+/// backend in its `backend` value. The latter allows several differently
+/// configured instances of the same backend.
+#[derive(Default)]
+pub struct BackendRegistry {
+    backends: Vec<BackendRegistration>,
+}
+
+impl BackendRegistry {
+    pub fn register(&mut self, registration: BackendRegistration) -> Result<()> {
+        if self.get(registration.name).is_some() {
+            crate::bail!(
+                InvalidArgument,
+                "Backend '{}' is registered twice",
+                registration.name
+            )
+        }
+
+        self.backends.push(registration);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BackendRegistration> {
+        self.backends.iter().find(|b| b.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &BackendRegistration> {
+        self.backends.iter()
+    }
+
+    fn names(&self) -> String {
+        self.backends
+            .iter()
+            .map(|b| b.name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Registered backend of `name` and its configuration from the profile.
+    /// Unknown configuration keys are reported here, so resolve each name
+    /// once.
+    pub fn resolve(&self, name: &str, profile: &Profile) -> Result<ResolvedBackend<'_>> {
+        let mut config = profile.backends.get(name).cloned();
+
+        let backend = match config.as_mut() {
+            Some(toml::Value::Table(table)) => match table.remove("backend") {
+                Some(toml::Value::String(s)) => s,
+                Some(_) => crate::bail!(
+                    InvalidArgument,
+                    "'backend' of [backends.{}] must be a string",
+                    name
+                ),
+                None => name.to_owned(),
+            },
+            Some(_) => crate::bail!(InvalidArgument, "[backends.{}] must be a table", name),
+            None => name.to_owned(),
+        };
+
+        let registration = self.get(&backend).ok_or_else(|| {
+            crate::err!(
+                NotExist,
+                "Unknown backend '{}', available backends: {}",
+                backend,
+                self.names()
+            )
+        })?;
+
+        if let Some(toml::Value::Table(table)) = &config {
+            for key in table.keys() {
+                if !registration.config.iter().any(|k| k.key == key) {
+                    warn!(
+                        "[backends.{}]: unknown key '{}' for backend '{}'",
+                        name, key, registration.name
+                    );
+                }
+            }
+        }
+
+        Ok(ResolvedBackend {
+            name: name.to_owned(),
+            registration,
+            config,
+        })
+    }
+
+    /// Resolve every `[backends.*]` table of the profile, ordered by name.
+    /// Fails if any of them names an unknown backend.
+    pub fn resolve_profile(&self, profile: &Profile) -> Result<Vec<ResolvedBackend<'_>>> {
+        let mut names: Vec<_> = profile.backends.keys().collect();
+        names.sort_unstable();
+
+        names
+            .into_iter()
+            .map(|name| self.resolve(name, profile))
+            .collect()
+    }
+
+    /// Resolve the backends selected by `names`, in that order. Other
+    /// `[backends.*]` tables of the profile which can not be resolved, e.g.
+    /// naming a backend not compiled in, are only warned about.
+    pub fn resolve_selected(
+        &self,
+        names: &[String],
+        profile: &Profile,
+    ) -> Result<Vec<ResolvedBackend<'_>>> {
+        let mut others: Vec<_> = profile
+            .backends
+            .keys()
+            .filter(|name| !names.contains(name))
+            .collect();
+        others.sort_unstable();
+
+        for name in others {
+            if let Err(e) = self.resolve(name, profile) {
+                warn!("[backends.{}] is not selected, ignored: {}", name, e);
+            }
+        }
+
+        names
+            .iter()
+            .map(|name| self.resolve(name, profile))
+            .collect()
+    }
+
+    /// Describe the backends configured by the profile
+    pub fn describe_profile(&self, profile: &Profile) -> Result<String> {
+        let mut ret = String::new();
+
+        for resolved in self.resolve_profile(profile)? {
+            writeln!(
+                ret,
+                "[backends.{}] -> {}",
+                resolved.name, resolved.registration.name
+            )?;
+
+            if let Some(toml::Value::Table(table)) = resolved.config {
+                for (key, value) in table.iter() {
+                    writeln!(ret, "    {} = {}", key, value)?;
+                }
+            }
+        }
+
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackendConfigKey, BackendRegistration, BackendRegistry};
+    use crate::profile::Profile;
+
+    fn registry() -> BackendRegistry {
+        let mut registry = BackendRegistry::default();
+        registry
+            .register(BackendRegistration {
+                name: "json",
+                description: "",
+                constructor: None,
+                config: &[BackendConfigKey {
+                    key: "pretty",
+                    kind: "bool",
+                    default: "false",
+                    description: "",
+                }],
+            })
+            .unwrap();
+        registry.register(BackendRegistration::null()).unwrap();
+        registry
+    }
+
+    fn profile(config: &str) -> Profile {
+        Profile::from_configs(vec![config]).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_alias() {
+        let registry = registry();
+        let profile = profile("[backends.api]\nbackend = \"json\"\npretty = true\n");
+
+        let resolved = registry.resolve("api", &profile).unwrap();
+        assert_eq!(resolved.name, "api");
+        assert_eq!(resolved.registration.name, "json");
+        assert_eq!(
+            resolved.config,
+            Some(toml::from_str("pretty = true").unwrap())
+        );
+
+        let resolved = registry.resolve("json", &profile).unwrap();
+        assert_eq!(resolved.registration.name, "json");
+        assert!(resolved.config.is_none());
+
+        let names: Vec<_> = registry
+            .resolve_profile(&profile)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(names, ["api"]);
+    }
+
+    #[test]
+    fn test_resolve_unknown() {
+        let registry = registry();
+
+        let err = registry.resolve("www", &Profile::default()).err().unwrap();
+        assert!(err.to_string().contains("Unknown backend 'www'"));
+
+        let profile = profile("[backends.api]\nbackend = \"jsonapi\"\n");
+        assert!(registry.resolve("api", &profile).is_err());
+        assert!(registry.resolve_profile(&profile).is_err());
+    }
+
+    #[test]
+    fn test_resolve_selected() {
+        let registry = registry();
+        let profile = profile(
+            "[backends.api]\nbackend = \"json\"\n\
+             [backends.www]\ntheme = \"dark\"\n\
+             [backends.site]\nbackend = \"www\"\n",
+        );
+        assert!(registry.resolve_profile(&profile).is_err());
+
+        let selected = ["api".to_owned(), "null".to_owned()];
+        let names: Vec<_> = registry
+            .resolve_selected(&selected, &profile)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.name, r.registration.name))
+            .collect();
+        assert_eq!(
+            names,
+            [("api".to_owned(), "json"), ("null".to_owned(), "null")]
+        );
+
+        let selected = ["site".to_owned()];
+        let err = registry
+            .resolve_selected(&selected, &profile)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Unknown backend 'www'"));
+    }
+
+    #[test]
+    fn test_resolve_invalid() {
+        let registry = registry();
+
+        let not_table = profile("[backends]\njson = true\n");
+        assert!(registry.resolve("json", &not_table).is_err());
+        assert!(registry.resolve_profile(&not_table).is_err());
+
+        let not_string = profile("[backends.api]\nbackend = 1\n");
+        assert!(registry.resolve("api", &not_string).is_err());
+    }
+
+    #[test]
+    fn test_register_twice() {
+        let mut registry = registry();
+        assert!(registry.register(BackendRegistration::null()).is_err());
+        assert_eq!(registry.iter().count(), 2);
+    }
+}
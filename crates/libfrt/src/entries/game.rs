@@ -2,20 +2,291 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 use super::link::Link;
-use super::media::{Image, Media};
-use super::raw::RawGame;
+use super::media::{Image, ImageSource, Media};
+use super::raw::{RawDate, RawGame};
 use super::Bundle;
 use crate::i18n::LangId;
+use crate::utils::date::days_in_month;
+use crate::utils::fs::{get_ctime, get_mtime};
 use crate::ContextData;
 
+lazy_static! {
+    pub(crate) static ref LANG_TAG_RE: Regex = Regex::new(r"^[A-Za-z]{2,3}(-[A-Za-z0-9]{2,8})*$").unwrap();
+}
+
 #[derive(Debug)]
 pub enum Description {
     Plain(String),
     Markdown(String),
 }
 
+#[derive(Deserialize, Serialize, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContentRating {
+    /// Suitable for all audiences
+    #[default]
+    General,
+    /// Contains suggestive or violent contents
+    Mature,
+    /// Contains sexually explicit contents
+    Adult,
+}
+
+impl ContentRating {
+    pub fn is_sensitive(&self) -> bool {
+        *self != ContentRating::General
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentRating::General => "general",
+            ContentRating::Mature => "mature",
+            ContentRating::Adult => "adult",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Platform {
+    Windows,
+    Macos,
+    Linux,
+    Android,
+    Ios,
+    Web,
+    Switch,
+    Playstation,
+    Xbox,
+}
+
+impl Platform {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::Windows => "windows",
+            Platform::Macos => "macos",
+            Platform::Linux => "linux",
+            Platform::Android => "android",
+            Platform::Ios => "ios",
+            Platform::Web => "web",
+            Platform::Switch => "switch",
+            Platform::Playstation => "playstation",
+            Platform::Xbox => "xbox",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReleaseStatus {
+    Released,
+    EarlyAccess,
+    InDevelopment,
+    Cancelled,
+}
+
+impl ReleaseStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReleaseStatus::Released => "released",
+            ReleaseStatus::EarlyAccess => "early-access",
+            ReleaseStatus::InDevelopment => "in-development",
+            ReleaseStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Monetization {
+    Free,
+    Paid,
+    Donation,
+}
+
+impl Monetization {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Monetization::Free => "free",
+            Monetization::Paid => "paid",
+            Monetization::Donation => "donation",
+        }
+    }
+}
+
+/// Release date with year, month or day precision
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct ReleaseDate {
+    pub year: u16,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+}
+
+impl ReleaseDate {
+    /// Parse `YYYY`, `YYYY-MM` or `YYYY-MM-DD`
+    pub fn parse(s: &str) -> Result<Self> {
+        let invalid = || crate::err!(InvalidArgument, "Invalid release date: '{}'", s);
+
+        let mut parts = s.trim().split('-');
+
+        let year = parts
+            .next()
+            .filter(|p| p.len() == 4)
+            .and_then(|p| p.parse::<u16>().ok())
+            .ok_or_else(invalid)?;
+
+        let month = match parts.next() {
+            Some(p) => Some(
+                p.parse::<u8>()
+                    .ok()
+                    .filter(|m| (1..=12).contains(m))
+                    .ok_or_else(invalid)?,
+            ),
+            None => None,
+        };
+
+        let day = match (parts.next(), month) {
+            (Some(p), Some(month)) => Some(
+                p.parse::<u8>()
+                    .ok()
+                    .filter(|d| (1..=days_in_month(year, month)).contains(d))
+                    .ok_or_else(invalid)?,
+            ),
+            _ => None,
+        };
+
+        if parts.next().is_some() {
+            return Err(invalid().into());
+        }
+
+        Ok(Self { year, month, day })
+    }
+}
+
+impl std::fmt::Display for ReleaseDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}", self.year)?;
+        if let Some(month) = self.month {
+            write!(f, "-{:02}", month)?;
+        }
+        if let Some(day) = self.day {
+            write!(f, "-{:02}", day)?;
+        }
+        Ok(())
+    }
+}
+
+/// Kind of relationship to another game, read as "the target is the X of this game"
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum RelationKind {
+    Prequel,
+    Sequel,
+    BaseGame,
+    Dlc,
+    Original,
+    Remake,
+    Series,
+    Related,
+}
+
+impl RelationKind {
+    /// The relationship from the target back to this game
+    pub fn inverse(&self) -> RelationKind {
+        match self {
+            RelationKind::Prequel => RelationKind::Sequel,
+            RelationKind::Sequel => RelationKind::Prequel,
+            RelationKind::BaseGame => RelationKind::Dlc,
+            RelationKind::Dlc => RelationKind::BaseGame,
+            RelationKind::Original => RelationKind::Remake,
+            RelationKind::Remake => RelationKind::Original,
+            RelationKind::Series => RelationKind::Series,
+            RelationKind::Related => RelationKind::Related,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationKind::Prequel => "prequel",
+            RelationKind::Sequel => "sequel",
+            RelationKind::BaseGame => "base-game",
+            RelationKind::Dlc => "dlc",
+            RelationKind::Original => "original",
+            RelationKind::Remake => "remake",
+            RelationKind::Series => "series",
+            RelationKind::Related => "related",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Relation {
+    pub kind: RelationKind,
+    /// Id of the target game
+    pub target: String,
+}
+
+/// The `state` value of a game entry, see `GameState`
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum GameStateKind {
+    #[default]
+    Active,
+    Delisted,
+    Expunged,
+    Redirected,
+}
+
+/// Lifecycle state of a game entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameState {
+    Active,
+    /// Page is kept, but left out of lists, search and feeds
+    Delisted { reason: Option<String> },
+    /// Game is removed, only a tombstone is kept for the old URL
+    Expunged { reason: Option<String> },
+    /// Game is renamed or replaced by another game
+    Redirected { target: String },
+}
+
+impl GameState {
+    /// Whether the game should appear in lists, search and feeds
+    pub fn is_listed(&self) -> bool {
+        *self == GameState::Active
+    }
+
+    /// Whether only a tombstone or redirect stub is kept for the game
+    pub fn is_tombstone(&self) -> bool {
+        matches!(
+            self,
+            GameState::Expunged { .. } | GameState::Redirected { .. }
+        )
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameState::Active => "active",
+            GameState::Delisted { .. } => "delisted",
+            GameState::Expunged { .. } => "expunged",
+            GameState::Redirected { .. } => "redirected",
+        }
+    }
+}
+
+/// Credit of a person or a studio for a game
+#[derive(Debug, Clone)]
+pub struct GameAuthor {
+    pub name: String,
+    pub roles: Vec<String>,
+    /// Whether the author has an entry of its own
+    pub standalone: bool,
+}
+
 #[derive(Debug)]
 pub struct GameMedia {
     pub sensitive: bool,
@@ -30,10 +301,34 @@ pub struct Game {
     pub brief_description: Option<String>,
     pub links: Vec<Link>,
     pub medias: Vec<GameMedia>,
-    pub thumbnail: Image,
+    /// Always available unless the game is a tombstone
+    pub thumbnail: Option<Image>,
+    pub rating: ContentRating,
+    pub state: GameState,
+
+    pub platforms: Vec<Platform>,
+    pub release_status: Option<ReleaseStatus>,
+    pub release_date: Option<ReleaseDate>,
+    /// Languages supported by the game itself, as lowercase language tags
+    pub languages: Vec<String>,
+    pub monetization: Option<Monetization>,
+
+    /// Relationships to other games, completed with the inverse relationships
+    /// declared by other games once all bundles are loaded
+    pub relations: Vec<Relation>,
+
+    pub authors: Vec<GameAuthor>,
 
     pub l10n: HashMap<LangId, GameL10n>,
 
+    /// Timestamp for the time this game was added.
+    /// Taken from git history if available, otherwise from file system.
+    pub added: Option<i64>,
+
+    /// Timestamp for last update of this game.
+    /// Taken from git history if available, otherwise from file system.
+    pub updated: Option<i64>,
+
     pub bundle_path: PathBuf,
     //pub dirty: bool,
 }
@@ -66,6 +361,60 @@ impl Game {
         raw_game: RawGame,
         bundle_path: PathBuf,
     ) -> Result<Self> {
+        let state = match raw_game.state {
+            GameStateKind::Active => GameState::Active,
+            GameStateKind::Delisted => GameState::Delisted {
+                reason: raw_game.state_reason,
+            },
+            GameStateKind::Expunged => GameState::Expunged {
+                reason: raw_game.state_reason,
+            },
+            GameStateKind::Redirected => GameState::Redirected {
+                target: raw_game.redirect_to.clone().ok_or_else(|| {
+                    crate::err!(InvalidArgument, "'redirect-to' is required for redirected game")
+                })?,
+            },
+        };
+
+        if raw_game.redirect_to.is_some() && !matches!(state, GameState::Redirected { .. }) {
+            crate::bail!(
+                InvalidArgument,
+                "'redirect-to' is only allowed for redirected game"
+            )
+        }
+
+        let thumbnail = match &raw_game.thumbnail {
+            Some(thumbnail) => Some(Image::from_str(thumbnail, Some(&bundle_path))?),
+            None if state.is_tombstone() => None,
+            None => crate::bail!(InvalidBundle, "Thumbnail is required for game '{}'", id),
+        };
+
+        let release_date = match &raw_game.release_date {
+            Some(RawDate::Year(year)) => Some(ReleaseDate::parse(&year.to_string())?),
+            Some(RawDate::Date(s)) => Some(ReleaseDate::parse(s)?),
+            None => None,
+        };
+
+        let mut languages = Vec::new();
+        for lang in raw_game.languages.iter() {
+            if !LANG_TAG_RE.is_match(lang) {
+                crate::bail!(InvalidArgument, "Invalid language tag: '{}'", lang)
+            }
+            languages.push(lang.to_lowercase());
+        }
+
+        let mut relations = Vec::new();
+        for (kind, targets) in raw_game.relations.into_iter() {
+            for target in targets.into_iter() {
+                if target == id {
+                    crate::bail!(InvalidArgument, "Game '{}' can not relate to itself", id)
+                }
+                relations.push(Relation { kind, target });
+            }
+        }
+        relations.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.target.cmp(&b.target)));
+        relations.dedup();
+
         let description = match raw_game.description_format {
             Some(format) => match format.as_str() {
                 "plain" => Ok(Description::Plain(raw_game.description)),
@@ -101,15 +450,168 @@ impl Game {
             brief_description: raw_game.brief_description,
             links: links,
             medias: medias,
-            thumbnail: Image::from_str(&raw_game.thumbnail, None, Some(&bundle_path))?,
+            thumbnail,
+            rating: raw_game.rating,
+            state,
+
+            platforms: raw_game.platforms,
+            release_status: raw_game.release_status,
+            release_date,
+            languages,
+            monetization: raw_game.monetization,
+
+            relations,
+
+            authors: raw_game
+                .authors
+                .into_iter()
+                .map(|a| GameAuthor {
+                    name: a.name,
+                    roles: a.role,
+                    standalone: a.standalone,
+                })
+                .collect(),
 
             l10n: HashMap::new(),
 
+            added: None,
+            updated: None,
+
             bundle_path: bundle_path,
             //dirty: true,
         })
     }
 
+    /// Facets of this game in `namespace:value` form, for filtering and search
+    pub fn facets(&self) -> Vec<String> {
+        let mut facets: Vec<String> = self
+            .platforms
+            .iter()
+            .map(|p| format!("platform:{}", p.as_str()))
+            .collect();
+
+        if let Some(status) = &self.release_status {
+            facets.push(format!("release-status:{}", status.as_str()));
+        }
+
+        if let Some(monetization) = &self.monetization {
+            facets.push(format!("monetization:{}", monetization.as_str()));
+        }
+
+        for lang in self.languages.iter() {
+            facets.push(format!("language:{}", lang));
+        }
+
+        for lang in self.playable_languages() {
+            facets.push(format!("playable:{}", lang));
+        }
+
+        facets
+    }
+
+    /// Languages the game is playable in, either natively or with a
+    /// localization patch
+    pub fn playable_languages(&self) -> Vec<&str> {
+        let mut langs: Vec<&str> = self
+            .languages
+            .iter()
+            .map(|l| l.as_str())
+            .chain(self.links.iter().filter_map(|l| l.patch.as_ref()).map(|p| p.lang.as_str()))
+            .collect();
+
+        langs.sort_unstable();
+        langs.dedup();
+        langs
+    }
+
+    pub fn localized_name(&self, lang: LangId) -> &str {
+        self.l10n
+            .get(&lang)
+            .and_then(|l| l.name.as_deref())
+            .unwrap_or(&self.name)
+    }
+
+    pub fn localized_description(&self, lang: LangId) -> &Description {
+        self.l10n
+            .get(&lang)
+            .and_then(|l| l.description.as_ref())
+            .unwrap_or(&self.description)
+    }
+
+    pub fn localized_brief_description(&self, lang: LangId) -> Option<&str> {
+        self.l10n
+            .get(&lang)
+            .and_then(|l| l.brief_description.as_deref())
+            .or(self.brief_description.as_deref())
+    }
+
+    /// Path of a bundle file in the output of backends, relative to the
+    /// site root
+    pub fn asset_path(&self, name: &str) -> String {
+        format!("assets/game/{}/{}", self.id, name)
+    }
+
+    /// URI of a file of the game: remote files as they are, bundle files
+    /// under `root`
+    pub fn file_uri(&self, root: &str, name: &str, remote: bool) -> String {
+        match remote {
+            true => name.to_owned(),
+            false => format!("{}/{}", root.trim_end_matches('/'), self.asset_path(name)),
+        }
+    }
+
+    /// URI of an image of the game, see `file_uri`.
+    /// Shared images are only supported by the WWW backend.
+    pub fn image_uri(&self, root: &str, image: &Image) -> Result<String> {
+        Ok(match &image.source {
+            ImageSource::Bundled(s) => self.file_uri(root, s, false),
+            ImageSource::Remote(s) => s.clone(),
+            ImageSource::LocalShared(s) => crate::bail!(
+                InvalidArgument,
+                "Shared image '{}' is only supported by the WWW backend",
+                s
+            ),
+        })
+    }
+
+    /// Names of the bundle files used by the thumbnail and medias
+    pub fn bundled_files(&self) -> Vec<&str> {
+        fn image(image: &Image) -> Option<&str> {
+            match &image.source {
+                ImageSource::Bundled(s) => Some(s),
+                _ => None,
+            }
+        }
+
+        let mut files: Vec<&str> = self.thumbnail.iter().filter_map(image).collect();
+
+        for gm in self.medias.iter() {
+            match &gm.media {
+                Media::Image(i) => files.extend(image(i)),
+                Media::HBox(images) => files.extend(images.iter().filter_map(image)),
+                Media::Video {
+                    sources,
+                    poster,
+                    captions,
+                } => {
+                    files.extend(sources.iter().filter(|s| !s.remote).map(|s| s.uri.as_str()));
+                    files.extend(poster.iter().filter_map(image));
+                    files.extend(captions.iter().filter(|c| !c.remote).map(|c| c.uri.as_str()));
+                }
+                Media::Youtube(_) => {}
+            }
+        }
+
+        files.sort_unstable();
+        files.dedup();
+        files
+    }
+
+    /// Whether the game itself is sensitive, regardless of its medias
+    pub fn is_sensitive(&self) -> bool {
+        self.rating.is_sensitive()
+    }
+
     pub fn from_bundle(data: &ContextData, path: &Path) -> Result<Game> {
         info!("Loading game bundle: {}", path.display());
 
@@ -126,10 +628,65 @@ impl Game {
             crate::bail!(InvalidBundle, "Can not found game.yaml")
         }
 
-        let raw_game = serde_yaml::from_str(&std::fs::read_to_string(path_game_yaml)?)?;
+        let raw_game = serde_yaml::from_str(&std::fs::read_to_string(&path_game_yaml)?)?;
+
+        let mut game = Game::build(data, id, raw_game, std::fs::canonicalize(path)?)?;
 
-        let game = Game::build(data, id, raw_game, std::fs::canonicalize(path)?)?;
+        game.added = get_ctime(&path_game_yaml).ok().map(|t| t as i64);
+        game.updated = get_mtime(&path_game_yaml).ok().map(|t| t as i64);
 
         Ok(game)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ContentRating, GameStateKind, ReleaseDate};
+    use crate::entries::raw::RawGame;
+
+    #[test]
+    fn test_release_date_parse() {
+        let d = ReleaseDate::parse("2021").unwrap();
+        assert_eq!((d.year, d.month, d.day), (2021, None, None));
+        assert_eq!(ReleaseDate::parse("2021-5").unwrap().to_string(), "2021-05");
+        assert_eq!(ReleaseDate::parse("2021-05-03").unwrap().to_string(), "2021-05-03");
+
+        assert!(ReleaseDate::parse("21").is_err());
+        assert!(ReleaseDate::parse("2021-13").is_err());
+        assert!(ReleaseDate::parse("2021-01-32").is_err());
+        assert!(ReleaseDate::parse("2021-02-31").is_err());
+        assert!(ReleaseDate::parse("2021-04-31").is_err());
+        assert!(ReleaseDate::parse("2021-02-29").is_err());
+        assert!(ReleaseDate::parse("1900-02-29").is_err());
+        assert!(ReleaseDate::parse("2020-02-29").is_ok());
+        assert!(ReleaseDate::parse("2000-02-29").is_ok());
+        assert!(ReleaseDate::parse("2021-04-30").is_ok());
+        assert!(ReleaseDate::parse("2021-01-01-01").is_err());
+        assert!(ReleaseDate::parse("May 2021").is_err());
+    }
+
+    #[test]
+    fn test_state_parse() {
+        let raw: RawGame = serde_yaml::from_str("name: Foo").unwrap();
+        assert_eq!(raw.state, GameStateKind::Active);
+
+        let raw: RawGame = serde_yaml::from_str("name: Foo\nstate: delisted").unwrap();
+        assert_eq!(raw.state, GameStateKind::Delisted);
+
+        assert!(serde_yaml::from_str::<RawGame>("name: Foo\nstate: removed").is_err());
+        assert!(serde_yaml::from_str::<RawGame>("name: Foo\nstate: Expunged").is_err());
+    }
+
+    #[test]
+    fn test_rating_parse() {
+        let raw: RawGame = serde_yaml::from_str("name: Foo").unwrap();
+        assert_eq!(raw.rating, ContentRating::General);
+        assert!(!raw.rating.is_sensitive());
+
+        let raw: RawGame = serde_yaml::from_str("name: Foo\nrating: mature").unwrap();
+        assert_eq!(raw.rating, ContentRating::Mature);
+        assert!(raw.rating.is_sensitive());
+
+        assert!(serde_yaml::from_str::<RawGame>("name: Foo\nrating: nsfw").is_err());
+    }
+}
@@ -1,12 +1,14 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use crate::utils::tengine;
+use crate::utils::strsim;
+use crate::utils::tengine::Template;
 use anyhow::Result;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use super::raw::RawLinkItem;
+use super::game::LANG_TAG_RE;
+use super::raw::{RawLinkItem, RawPatch};
 use crate::i18n::LangId;
 
 #[derive(Serialize, Debug)]
@@ -16,6 +18,163 @@ pub struct Link {
     #[serde(skip)]
     pub rule: Option<Rc<StockLinkRule>>,
     pub variables: HashMap<String, String>,
+    /// Set when the link was written as a full URL recognized by a stock rule
+    #[serde(skip)]
+    pub rewrite: Option<LinkRewrite>,
+    /// Set when a stock link hint could not be applied and the link
+    /// fell back to a custom link
+    #[serde(skip)]
+    pub hint_problem: Option<HintProblem>,
+    pub patch: Option<LocalizationPatch>,
+}
+
+/// A third-party patch which localizes the game
+#[derive(Serialize, Debug, Clone)]
+pub struct LocalizationPatch {
+    /// Target language, as lowercase language tag
+    pub lang: String,
+    pub author: Option<String>,
+    /// Translated percentage, 0 to 100
+    pub completeness: Option<u8>,
+}
+
+impl LocalizationPatch {
+    fn from_raw(raw: RawPatch) -> Result<Self> {
+        if !LANG_TAG_RE.is_match(&raw.lang) {
+            crate::bail!(InvalidArgument, "Invalid language tag of patch: '{}'", raw.lang)
+        }
+
+        if let Some(completeness) = raw.completeness.filter(|c| *c > 100) {
+            crate::bail!(
+                InvalidArgument,
+                "Completeness of patch must be 0 to 100, got {}",
+                completeness
+            )
+        }
+
+        Ok(Self {
+            lang: raw.lang.to_lowercase(),
+            author: raw.author,
+            completeness: raw.completeness,
+        })
+    }
+
+    /// Whether the patch targets readers of the site in `lang`.
+    /// A patch without region (e.g. `zh`) matches every region.
+    pub fn matches(&self, lang: LangId) -> bool {
+        self.lang == lang.as_str()
+            || self.lang == lang.as_bcp47().to_lowercase()
+            || self.lang == lang.as_str_noregion()
+    }
+}
+
+impl Link {
+    pub fn category(&self) -> LinkCategory {
+        match &self.patch {
+            Some(_) => LinkCategory::LocalizationPatch,
+            None => self.rule.as_ref().map(|r| r.category).unwrap_or_default(),
+        }
+    }
+
+    pub fn weight(&self) -> i32 {
+        self.rule.as_ref().map(|r| r.weight).unwrap_or_default()
+    }
+
+    /// Label in `lang`, fallback to the default language and then the URI
+    pub fn localized_label(&self, lang: LangId) -> &str {
+        self.label
+            .get(&lang)
+            .or_else(|| self.label.get(&LangId::default()))
+            .unwrap_or(&self.uri)
+    }
+
+    /// Whether the link is relevant to readers of the site in `lang`
+    pub fn is_available(&self, lang: LangId) -> bool {
+        match &self.rule {
+            Some(rule) => rule.langs.is_empty() || rule.langs.contains(&lang),
+            None => true,
+        }
+    }
+
+    /// Whether the link is a localization patch for readers in `lang`
+    pub fn is_highlighted(&self, lang: LangId) -> bool {
+        self.patch.as_ref().is_some_and(|p| p.matches(lang))
+    }
+
+    /// Links available in `lang`, in display order: by category, with
+    /// localization patches for `lang` first, then by weight
+    pub fn displayed(links: &[Link], lang: LangId) -> Vec<&Link> {
+        let mut links: Vec<&Link> = links.iter().filter(|l| l.is_available(lang)).collect();
+        links.sort_by_key(|l| (l.category(), !l.is_highlighted(lang), l.weight()));
+        links
+    }
+
+    fn custom(label: String, uri: String) -> Self {
+        Self {
+            label: HashMap::from([(LangId::default(), label)]),
+            uri,
+            rule: None,
+            variables: HashMap::new(),
+            rewrite: None,
+            hint_problem: None,
+            patch: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintProblem {
+    pub message: String,
+    /// Closest rule name which would be accepted
+    pub suggestion: Option<String>,
+}
+
+/// How stock link hints (`.name`) which can not be applied are handled
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum HintPolicy {
+    /// Unknown rules fall back to custom links with an error logged,
+    /// mismatched URIs fail the build
+    #[default]
+    Lenient,
+    /// Both unknown rules and mismatched URIs fall back to custom links,
+    /// to report all of them at once while migrating old data
+    Migration,
+    /// Unknown rules and mismatched URIs fail the build
+    Strict,
+}
+
+/// A suggested rewrite of a link item in game.yaml
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkRewrite {
+    pub from: String,
+    pub to: String,
+}
+
+/// Kind of site a link points to, in display order
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkCategory {
+    Official,
+    Store,
+    LocalizationPatch,
+    Community,
+    Social,
+    #[default]
+    Other,
+}
+
+impl LinkCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkCategory::Official => "official",
+            LinkCategory::Store => "store",
+            LinkCategory::LocalizationPatch => "localization-patch",
+            LinkCategory::Community => "community",
+            LinkCategory::Social => "social",
+            LinkCategory::Other => "other",
+        }
+    }
 }
 
 #[derive(Deserialize, Default, Debug)]
@@ -23,12 +182,106 @@ pub struct Link {
 pub struct StockLinkRule {
     pub name: String,
     pub icon: String,
+    /// Localized labels, filled from the config once the rule is added
+    #[serde(skip)]
     pub label: HashMap<LangId, String>,
+    /// Labels keyed by language tag, `_` for the default language
+    #[serde(rename = "label")]
+    raw_label: HashMap<String, String>,
     #[serde(with = "serde_regex")]
     pub regex: Option<Regex>,
     pub www_href: String,
     pub inference: bool,
     pub passthrough: bool,
+    /// Full URLs which are normalized to the pseudo-URI of this rule
+    #[serde(with = "serde_regex")]
+    pub reverse: Vec<Regex>,
+    /// Template of the pseudo-URI, rendered with captures of `reverse`.
+    /// Not used by passthrough rules, which keep the URL as is.
+    pub canonical: String,
+    pub category: LinkCategory,
+    /// Order of links in the same category, lower first
+    pub weight: i32,
+    /// Site languages the link is relevant to, empty for all languages.
+    /// Filled from the config once the rule is added.
+    #[serde(skip)]
+    pub langs: Vec<LangId>,
+    /// Language tags of `langs`
+    #[serde(rename = "langs")]
+    raw_langs: Vec<String>,
+    /// Inference and reverse rules with higher priority are tried first
+    pub priority: i32,
+    /// Position in the stock configs, breaks ties of priority
+    #[serde(skip)]
+    pub order: usize,
+    #[serde(skip)]
+    templates: RuleTemplates,
+}
+
+/// Templates of a rule, compiled once the rule is added
+#[derive(Default, Debug)]
+struct RuleTemplates {
+    href: Template,
+    label: HashMap<LangId, Template>,
+    canonical: Template,
+}
+
+/// Literal text an anchored regex starts with, `None` if not anchored
+fn literal_prefix(re: &Option<Regex>) -> Option<String> {
+    let mut chars = re.as_ref()?.as_str().strip_prefix('^')?.chars();
+    let mut prefix = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c) if !c.is_alphanumeric() => prefix.push(c),
+                _ => break,
+            },
+            // an alternative may start with anything
+            '|' => {
+                prefix.clear();
+                break;
+            }
+            '.' | '+' | '*' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '$' | '^' => {
+                // a quantifier also applies to the last literal char
+                if matches!(c, '?' | '*' | '{') {
+                    prefix.pop();
+                }
+                break;
+            }
+            c => prefix.push(c),
+        }
+    }
+
+    Some(prefix)
+}
+
+/// Template variables provided by captures of `re`
+fn regex_variables(re: &Regex) -> Vec<String> {
+    (0..re.captures_len())
+        .map(|i| format!("_{i}"))
+        .chain(re.capture_names().flatten().map(|n| n.to_owned()))
+        .collect()
+}
+
+fn capture_variables(re: &Regex, uri: &str) -> Option<HashMap<String, String>> {
+    let caps = re.captures(uri)?;
+
+    let mut variables: HashMap<String, String> = caps
+        .iter()
+        .enumerate()
+        .map(|(i, group)| {
+            let value = group.map(|g| g.as_str()).unwrap_or_default();
+            (format!("_{i}"), value.to_owned())
+        })
+        .collect();
+
+    variables.extend(re.capture_names().flatten().map(|n| {
+        let value = caps.name(n).map(|g| g.as_str()).unwrap_or_default();
+        (n.to_owned(), value.to_owned())
+    }));
+
+    Some(variables)
 }
 
 impl StockLinkRule {
@@ -39,56 +292,172 @@ impl StockLinkRule {
         }
     }
 
-    pub fn build_link(self: &Rc<Self>, uri: &str) -> Result<Link> {
-        if self.passthrough {
-            Ok(Link {
-                label: self.label.clone(),
-                uri: uri.to_owned(),
-                rule: Some(self.clone()),
-                variables: HashMap::new(),
-            })
-        } else {
-            let mut label = self.label.clone();
-            let mut final_uri = self.www_href.to_owned();
-            let mut variables = HashMap::new();
-
-            if let Some(re) = &self.regex {
-                let caps = re.captures(uri).ok_or_else(|| {
-                    crate::err!(
-                        InvalidArgument,
-                        "Failed to parse URI '{}' by rule '{}': regex not match",
-                        uri,
-                        self.name
-                    )
-                })?;
-
-                let mut i = 0;
-                for group in caps.iter() {
-                    variables.insert(format!("_{i}"), group.unwrap().as_str().to_owned());
-                    i += 1;
+    /// Parse and check labels and templates of the rule, returns all problems found
+    fn compile(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let variables = match (&self.regex, self.passthrough) {
+            (Some(re), false) => regex_variables(re),
+            _ => Vec::new(),
+        };
+
+        let compile = |template: &str, variables: &[String]| -> Result<Template> {
+            let t = Template::parse(template)?;
+            t.check_variables(variables)?;
+            Ok(t)
+        };
+
+        let mut templates = RuleTemplates::default();
+
+        if self.inference && !self.passthrough && self.regex.is_none() {
+            problems.push("regex is required for inference rule".to_owned());
+        }
+
+        if !self.passthrough {
+            match compile(&self.www_href, &variables) {
+                Ok(t) => templates.href = t,
+                Err(e) => problems.push(format!("www_href: {e}")),
+            }
+        }
+
+        let mut keys: Vec<&String> = self.raw_label.keys().collect();
+        keys.sort_unstable();
+
+        for key in keys {
+            let lang = match key.as_str() {
+                "_" => LangId::default(),
+                key => match LangId::from_tag(key) {
+                    Some(lang) => lang,
+                    None => {
+                        problems.push(format!("invalid language key '{key}' of label"));
+                        continue;
+                    }
+                },
+            };
+
+            let label = &self.raw_label[key];
+            match compile(label, &variables) {
+                Ok(t) => {
+                    templates.label.insert(lang, t);
+                    self.label.insert(lang, label.clone());
                 }
+                Err(e) => problems.push(format!("label '{key}': {e}")),
+            }
+        }
 
-                let named_variables: HashMap<String, String> = re
-                    .capture_names()
-                    .flatten()
-                    .map(|n| (n.to_owned(), caps.name(n).unwrap().as_str().to_owned()))
-                    .collect();
+        self.langs.clear();
+        for tag in self.raw_langs.iter() {
+            match LangId::from_tag(tag) {
+                Some(lang) => self.langs.push(lang),
+                None => problems.push(format!("invalid language '{tag}' of langs")),
+            }
+        }
 
-                variables.extend(named_variables);
+        if !self.reverse.is_empty() && !self.passthrough {
+            if self.canonical.is_empty() {
+                problems.push("canonical is required for reverse patterns".to_owned());
+            }
 
-                for (_, l10n_label) in label.iter_mut() {
-                    *l10n_label = tengine::simple_template_render(&l10n_label, &variables)?;
+            for re in self.reverse.iter() {
+                match compile(&self.canonical, &regex_variables(re)) {
+                    Ok(t) => templates.canonical = t,
+                    Err(e) => problems.push(format!("canonical, for reverse '{re}': {e}")),
                 }
+            }
+        }
+
+        self.templates = templates;
+        problems
+    }
 
-                final_uri = tengine::simple_template_render(&final_uri, &variables)?;
+    /// Whether inference regexes of two rules might match the same URI.
+    /// Literal prefixes of anchored regexes are compared, unanchored regexes
+    /// are assumed to overlap with anything.
+    fn may_overlap(&self, other: &StockLinkRule) -> bool {
+        match (literal_prefix(&self.regex), literal_prefix(&other.regex)) {
+            (Some(a), Some(b)) => a.starts_with(&b) || b.starts_with(&a),
+            _ => true,
+        }
+    }
+
+    pub fn build_link(self: &Rc<Self>, uri: &str) -> Result<Link> {
+        let variables = match (&self.regex, self.passthrough) {
+            (Some(re), false) => capture_variables(re, uri).ok_or_else(|| {
+                crate::err!(
+                    InvalidArgument,
+                    "Failed to parse URI '{}' by rule '{}': regex not match",
+                    uri,
+                    self.name
+                )
+            })?,
+            _ => HashMap::new(),
+        };
+
+        let mut label = HashMap::new();
+        for (lang, template) in self.templates.label.iter() {
+            label.insert(*lang, template.render(&variables)?);
+        }
+
+        let final_uri = match self.passthrough {
+            true => uri.to_owned(),
+            false => self.templates.href.render(&variables)?,
+        };
+
+        Ok(Link {
+            label,
+            uri: final_uri,
+            rule: Some(self.clone()),
+            variables,
+            rewrite: None,
+            hint_problem: None,
+            patch: None,
+        })
+    }
+
+    /// Normalize a full URL to the pseudo-URI of this rule,
+    /// `None` if no reverse pattern matches.
+    pub fn reverse_uri(&self, uri: &str) -> Result<Option<String>> {
+        for re in self.reverse.iter() {
+            if let Some(variables) = capture_variables(re, uri) {
+                return Ok(Some(match self.passthrough {
+                    true => uri.to_owned(),
+                    false => self.templates.canonical.render(&variables)?,
+                }));
             }
+        }
+
+        Ok(None)
+    }
+
+    /// Build link from a full URL matched by `reverse`, recording the rewrite.
+    fn build_reversed_link(self: &Rc<Self>, uri: &str) -> Result<Option<Link>> {
+        let Some(normalized) = self.reverse_uri(uri)? else {
+            return Ok(None);
+        };
+
+        let mut link = self.build_link(&normalized)?;
+        link.rewrite = Some(LinkRewrite {
+            from: uri.to_owned(),
+            to: match self.passthrough {
+                true => format!("{{ name: .{}, uri: {} }}", self.name, normalized),
+                false => normalized,
+            },
+        });
+
+        Ok(Some(link))
+    }
 
-            Ok(Link {
-                label: label,
-                uri: final_uri,
-                rule: Some(self.clone()),
-                variables: variables,
-            })
+    /// Item of `links` in `game.yaml` for `uri` inferred by this rule: the
+    /// pseudo-URI, or a `.name` hint with `uri` for passthrough rules.
+    pub fn link_item(&self, uri: &str) -> serde_yaml::Value {
+        match self.passthrough {
+            true => {
+                let mut item = serde_yaml::Mapping::new();
+                item.insert("name".into(), format!(".{}", self.name).into());
+                item.insert("uri".into(), uri.into());
+                serde_yaml::Value::Mapping(item)
+            }
+            false => uri.into(),
         }
     }
 }
@@ -97,84 +466,262 @@ impl StockLinkRule {
 pub struct LinkRuleManager {
     pub rules: HashMap<String, Rc<StockLinkRule>>,
     pub inference_rules: Vec<Rc<StockLinkRule>>,
+    pub reverse_rules: Vec<Rc<StockLinkRule>>,
+    pub hint_policy: HintPolicy,
+    /// Number of rules ever added, for the order of the next rule
+    added: usize,
 }
 
 impl LinkRuleManager {
-    pub fn add_rule(&mut self, rule: StockLinkRule) -> Result<()> {
+    /// Validate and add a rule. Every problem is logged with the rule name
+    /// before failing.
+    pub fn add_rule(&mut self, mut rule: StockLinkRule) -> Result<()> {
+        let problems = rule.compile();
+
+        if !rule.passthrough && rule.inference {
+            for other in self.inference_rules.iter() {
+                if rule.priority == other.priority && rule.may_overlap(other) {
+                    warn!(
+                        "StockLink '{}': inference regex may overlap with rule '{}' of the same priority, '{}' is tried first as it comes first",
+                        rule.name, other.name, other.name
+                    );
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            for problem in problems.iter() {
+                error!("StockLink '{}': {}", rule.name, problem);
+            }
+
+            crate::bail!(
+                InvalidArgument,
+                "StockLink: {} problems in rule '{}'",
+                problems.len(),
+                rule.name
+            )
+        }
+
+        rule.order = self.added;
+        self.added += 1;
+
+        // a rule of a later stock config replaces the one with the same name
+        if self.rules.contains_key(&rule.name) {
+            self.inference_rules.retain(|r| r.name != rule.name);
+            self.reverse_rules.retain(|r| r.name != rule.name);
+        }
+
         let rule = Rc::new(rule);
 
         self.rules.insert(rule.name.to_owned(), rule.clone());
 
+        let key = |r: &Rc<StockLinkRule>| (std::cmp::Reverse(r.priority), r.order);
+
         if !rule.passthrough && rule.inference {
-            let regex = rule
-                .regex
-                .as_ref()
-                .ok_or_else(|| {
-                    crate::err!(
-                        InvalidArgument,
-                        "StockLink: regex is required for inference rule",
-                    )
-                })?
-                .clone();
             self.inference_rules.push(rule.clone());
+            self.inference_rules.sort_by_key(key);
+        }
+
+        if !rule.reverse.is_empty() {
+            self.reverse_rules.push(rule.clone());
+            self.reverse_rules.sort_by_key(key);
         }
 
         Ok(())
     }
 
-    pub fn build_link(&self, raw_link: RawLinkItem) -> Result<Link> {
+    /// Explain how a link written as `uri` is resolved, line by line
+    pub fn explain(&self, uri: &str) -> Vec<String> {
+        let mut ret = Vec::new();
+        let mut used = false;
+
+        ret.push("Inference rules, in the order they are tried:".to_owned());
+        for rule in self.inference_rules.iter() {
+            let state = match rule.match_uri(uri) {
+                true if !used => {
+                    used = true;
+                    "match, used"
+                }
+                true => "match, shadowed",
+                false => "no match",
+            };
+
+            ret.push(format!(
+                "  {} (priority {}, order {}): regex '{}': {}",
+                rule.name,
+                rule.priority,
+                rule.order,
+                rule.regex.as_ref().map(|r| r.as_str()).unwrap_or_default(),
+                state
+            ));
+        }
+
+        ret.push("Reverse patterns, tried if no inference rule matches:".to_owned());
+        for rule in self.reverse_rules.iter() {
+            for re in rule.reverse.iter() {
+                let state = match re.is_match(uri) {
+                    true if !used => {
+                        used = true;
+                        "match, used"
+                    }
+                    true => "match, shadowed",
+                    false => "no match",
+                };
+
+                ret.push(format!(
+                    "  {} (priority {}, order {}): reverse '{}': {}",
+                    rule.name,
+                    rule.priority,
+                    rule.order,
+                    re.as_str(),
+                    state
+                ));
+            }
+        }
+
+        match self.build_link(RawLinkItem::Auto(uri.to_owned())) {
+            Ok(link) => {
+                let name = link.rule.as_ref().map(|r| r.name.as_str()).unwrap_or_default();
+                ret.push(format!("Resolved by rule '{}' to '{}'", name, link.uri));
+
+                if let Some(rewrite) = &link.rewrite {
+                    ret.push(format!("Suggested to be written as '{}'", rewrite.to));
+                }
+            }
+            Err(e) => ret.push(format!("Not resolved: {e}")),
+        }
+
+        ret
+    }
+
+    /// Closest rule name for a stock link hint which can not be applied.
+    /// Rules accepting `uri` are preferred over rules with a similar name.
+    pub fn suggest_rule(&self, rule_name: &str, uri: Option<&str>) -> Option<String> {
+        let mut names: Vec<&str> = self.rules.keys().map(|k| k.as_str()).collect();
+        names.sort_unstable();
+
+        if let Some(uri) = uri {
+            let accepting: Vec<&str> = names
+                .iter()
+                .copied()
+                .filter(|n| *n != rule_name)
+                .filter(|n| {
+                    let rule = &self.rules[*n];
+                    (rule.regex.is_some() && rule.match_uri(uri))
+                        || rule.reverse.iter().any(|re| re.is_match(uri))
+                })
+                .collect();
+
+            if let Some(name) = accepting
+                .iter()
+                .min_by_key(|n| strsim::levenshtein(rule_name, n))
+            {
+                return Some(name.to_string());
+            }
+        }
+
+        strsim::closest(rule_name, names).map(|s| s.to_owned())
+    }
+
+    /// Stock rule resolving a link to `uri` without hint, and the URI to
+    /// write with it: a pseudo-URI such as `steam:123`, or `uri` itself for
+    /// passthrough rules. `None` if no rule recognizes `uri`.
+    pub fn infer(&self, uri: &str) -> Result<Option<(Rc<StockLinkRule>, String)>> {
+        if let Some(rule) = self.inference_rules.iter().find(|r| r.match_uri(uri)) {
+            return Ok(Some((rule.clone(), uri.to_owned())));
+        }
+
+        for rule in self.reverse_rules.iter() {
+            if let Some(normalized) = rule.reverse_uri(uri)? {
+                return Ok(Some((rule.clone(), normalized)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Same as `infer`, with the item to write in `links` for `uri`, see
+    /// `StockLinkRule::link_item`
+    pub fn stock_link_item(
+        &self,
+        uri: &str,
+    ) -> Result<Option<(Rc<StockLinkRule>, serde_yaml::Value)>> {
+        Ok(self.infer(uri)?.map(|(rule, uri)| {
+            let item = rule.link_item(&uri);
+            (rule, item)
+        }))
+    }
+
+    /// Build link from a full URL recognized by a reverse pattern of any rule.
+    pub fn build_reversed_link(&self, uri: &str) -> Result<Option<Link>> {
+        for rule in self.reverse_rules.iter() {
+            if let Some(link) = rule.build_reversed_link(uri)? {
+                return Ok(Some(link));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn build_link(&self, mut raw_link: RawLinkItem) -> Result<Link> {
+        let patch = match &mut raw_link {
+            RawLinkItem::Custom { patch, .. } => patch.take(),
+            RawLinkItem::Auto(_) => None,
+        };
+
+        let mut link = self.build_plain_link(raw_link)?;
+        link.patch = patch.map(LocalizationPatch::from_raw).transpose()?;
+
+        Ok(link)
+    }
+
+    fn build_plain_link(&self, raw_link: RawLinkItem) -> Result<Link> {
         Ok(match raw_link {
-            RawLinkItem::Custom { name, uri } => {
+            RawLinkItem::Custom { name, uri, .. } => {
                 if name.starts_with('.') {
                     // stock link hint
                     let rule_name = &name[1..];
 
-                    match self.rules.get(rule_name) {
-                        Some(rule) => {
-                            if !rule.match_uri(uri.as_str()) {
-                                crate::bail!(
-                                    InvalidArgument,
-                                    "URI '{}' not matchs rule '{}'",
-                                    uri,
-                                    rule_name
-                                )
-                            }
-
-                            rule.build_link(uri.as_str())?
-                        }
-                        None => {
-                            error!("impl LinkRuleManager: fn build_link(): STUB!"); /* TODO */
-                            error!("Required stock link rule: {}", rule_name);
-                            Link {
-                                label: HashMap::from([(
-                                    LangId::default(),
-                                    "Unimplemented stock link".into(),
-                                )]),
-                                uri: uri.to_owned(),
-                                rule: None,
-                                variables: HashMap::new(),
-                            }
+                    let problem = match self.rules.get(rule_name) {
+                        Some(rule) if rule.match_uri(uri.as_str()) => {
+                            return rule.build_link(uri.as_str())
                         }
-                    }
+                        Some(rule) => match rule.build_reversed_link(uri.as_str())? {
+                            Some(link) => return Ok(link),
+                            None => HintProblem {
+                                message: format!("URI '{uri}' not matchs rule '{rule_name}'"),
+                                suggestion: self.suggest_rule(rule_name, Some(uri.as_str())),
+                            },
+                        },
+                        None => HintProblem {
+                            message: format!("Link rule '{rule_name}' not found"),
+                            suggestion: self.suggest_rule(rule_name, Some(uri.as_str())),
+                        },
+                    };
 
-                    /* TODO: Revert to below codes after old data migration/transition
-                    let rule = self.rules.get(rule_name)
-                        .ok_or_else(|| Error::new(ErrorKind::NotExist,
-                            format!("Link rule '{rule_name}' not found").as_str()))?;
+                    let mismatched = self.rules.contains_key(rule_name);
+                    let hint = match &problem.suggestion {
+                        Some(s) => format!("{}, did you mean '.{}'?", problem.message, s),
+                        None => problem.message.clone(),
+                    };
 
-                    if !rule.match_uri(uri.as_str()) {
-                        return Err(Error::new(ErrorKind::InvalidArgument,
-                            format!("URI '{uri}' not matchs rule '{rule_name}'").as_str()).into())
+                    match (self.hint_policy, mismatched) {
+                        (HintPolicy::Strict, false) => crate::bail!(NotExist, "{}", hint),
+                        (HintPolicy::Strict | HintPolicy::Lenient, true) => {
+                            crate::bail!(InvalidArgument, "{}", hint)
+                        }
+                        (HintPolicy::Lenient, false) => error!("{hint}"),
+                        // reported by `frt lint --migration`
+                        (HintPolicy::Migration, _) => {}
                     }
 
-                    rule.build_link(uri.as_str())?*/
+                    let mut link = Link::custom("Unimplemented stock link".into(), uri);
+                    link.hint_problem = Some(problem);
+                    link
+                } else if let Some(link) = self.build_reversed_link(uri.as_str())? {
+                    link
                 } else {
-                    Link {
-                        label: HashMap::from([(LangId::default(), name)]),
-                        uri,
-                        rule: None,
-                        variables: HashMap::new(),
-                    }
+                    Link::custom(name, uri)
                 }
             }
             RawLinkItem::Auto(uri) => {
@@ -189,9 +736,288 @@ impl LinkRuleManager {
 
                 match matched_rule {
                     Some(rule) => rule.build_link(uri.as_str())?,
-                    None => crate::bail!(NotExist, "Inference failed. No rule matchs '{}'.", uri),
+                    None => self.build_reversed_link(uri.as_str())?.ok_or_else(|| {
+                        crate::err!(NotExist, "Inference failed. No rule matchs '{}'.", uri)
+                    })?,
                 }
             }
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{HintPolicy, Link, LinkRuleManager, StockLinkRule};
+    use crate::entries::raw::{RawLinkItem, RawPatch};
+    use crate::i18n::LangId;
+    use crate::testing::STOCK;
+    use crate::ContextData;
+
+    fn manager(hint_policy: HintPolicy) -> LinkRuleManager {
+        let mut data = ContextData::default();
+        data.load_stock_str(STOCK, "stock.toml").unwrap();
+        data.link_rules.hint_policy = hint_policy;
+        data.link_rules
+    }
+
+    fn hint(name: &str, uri: &str) -> RawLinkItem {
+        RawLinkItem::Custom {
+            name: name.to_owned(),
+            uri: uri.to_owned(),
+            patch: None,
+        }
+    }
+
+    #[test]
+    fn test_hint_policy() {
+        for policy in [
+            HintPolicy::Lenient,
+            HintPolicy::Migration,
+            HintPolicy::Strict,
+        ] {
+            let rules = manager(policy);
+            let raw = hint(".steam", "https://store.steampowered.com/app/1");
+            let link = rules.build_link(raw).unwrap();
+            assert_eq!(link.rule.unwrap().name, "steam");
+            assert!(link.hint_problem.is_none());
+        }
+
+        let unknown = || hint(".stem", "steam:1");
+        let mismatched = || hint(".twitter", "steam:1");
+
+        let rules = manager(HintPolicy::Lenient);
+        let link = rules.build_link(unknown()).unwrap();
+        assert!(link.rule.is_none());
+        assert_eq!(link.uri, "steam:1");
+        let problem = link.hint_problem.unwrap();
+        assert_eq!(problem.message, "Link rule 'stem' not found");
+        assert_eq!(problem.suggestion.as_deref(), Some("steam"));
+        let e = rules.build_link(mismatched()).unwrap_err().to_string();
+        assert!(e.contains("URI 'steam:1' not matchs rule 'twitter', did you mean '.steam'?"));
+
+        let rules = manager(HintPolicy::Migration);
+        for (raw, message) in [
+            (unknown(), "Link rule 'stem' not found"),
+            (mismatched(), "URI 'steam:1' not matchs rule 'twitter'"),
+        ] {
+            let problem = rules.build_link(raw).unwrap().hint_problem.unwrap();
+            assert_eq!(problem.message, message);
+            assert_eq!(problem.suggestion.as_deref(), Some("steam"));
+        }
+
+        let rules = manager(HintPolicy::Strict);
+        let e = rules.build_link(unknown()).unwrap_err().to_string();
+        assert!(e.contains("Link rule 'stem' not found, did you mean '.steam'?"));
+        assert!(rules.build_link(mismatched()).is_err());
+    }
+
+    /// Passthrough rules of every category, some of them for a language only
+    const DISPLAY_STOCK: &str = r#"
+[link.forum]
+category = "community"
+passthrough = true
+
+[link.forum-cn]
+category = "community"
+weight = -1
+langs = ["zh-cn"]
+passthrough = true
+
+[link.store-b]
+category = "store"
+weight = 2
+passthrough = true
+
+[link.store-a]
+category = "store"
+weight = 1
+passthrough = true
+
+[link.website]
+category = "official"
+passthrough = true
+"#;
+
+    #[test]
+    fn test_displayed() {
+        let mut data = ContextData::default();
+        data.load_stock_str(DISPLAY_STOCK, "stock.toml").unwrap();
+
+        let patch = |lang: &str| RawLinkItem::Custom {
+            name: format!("Patch {lang}"),
+            uri: format!("https://patch.example.com/{lang}"),
+            patch: Some(RawPatch {
+                lang: lang.to_owned(),
+                author: None,
+                completeness: None,
+            }),
+        };
+        let links: Vec<Link> = [
+            hint("Custom", "https://example.com/"),
+            patch("en"),
+            hint(".forum", "https://forum.example.com/"),
+            hint(".store-b", "https://b.example.com/"),
+            patch("zh-cn"),
+            hint(".forum-cn", "https://forum.example.cn/"),
+            hint(".website", "https://example.com/game"),
+            hint(".store-a", "https://a.example.com/"),
+        ]
+        .into_iter()
+        .map(|raw| data.link_rules.build_link(raw).unwrap())
+        .collect();
+
+        let displayed = |lang: &str| -> Vec<String> {
+            Link::displayed(&links, LangId::from_tag(lang).unwrap())
+                .iter()
+                .map(|l| l.localized_label(LangId::default()).to_owned())
+                .collect()
+        };
+
+        assert_eq!(
+            displayed("en"),
+            [
+                "https://example.com/game",
+                "https://a.example.com/",
+                "https://b.example.com/",
+                "Patch en",
+                "Patch zh-cn",
+                "https://forum.example.com/",
+                "Custom",
+            ]
+        );
+        assert_eq!(
+            displayed("zh-cn"),
+            [
+                "https://example.com/game",
+                "https://a.example.com/",
+                "https://b.example.com/",
+                "Patch zh-cn",
+                "Patch en",
+                "https://forum.example.cn/",
+                "https://forum.example.com/",
+                "Custom",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_langs_invalid() {
+        let mut data = ContextData::default();
+        let stock = "[link.forum]\npassthrough = true\nlangs = [\"zh-cn\", \"xx-yy\"]\n";
+        let e = data.load_stock_str(stock, "stock.toml").unwrap_err();
+        let expected = "1 invalid link rules in 'stock.toml'";
+        assert!(e.to_string().contains(expected));
+        assert!(!data.link_rules.rules.contains_key("forum"));
+
+        let mut rule: StockLinkRule = toml::from_str("langs = [\"zh-cn\", \"xx-yy\"]").unwrap();
+        assert_eq!(rule.compile(), ["invalid language 'xx-yy' of langs"]);
+        assert_eq!(rule.langs, [LangId::ZhCn]);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut data = ContextData::default();
+        data.load_stock_str(STOCK, "stock.toml").unwrap();
+        let itch = r#"
+[link.itch]
+category = "store"
+passthrough = true
+reverse = ['^https://[a-z0-9-]+\.itch\.io/']
+"#;
+        data.load_stock_str(itch, "itch.toml").unwrap();
+        let rules = &data.link_rules;
+
+        let reverse = |name: &str, uri: &str| rules.rules[name].reverse_uri(uri).unwrap();
+        let auto = |uri: &str| rules.build_link(RawLinkItem::Auto(uri.to_owned())).unwrap();
+
+        let uri = "https://store.steampowered.com/app/100/Foo_Game/";
+        assert_eq!(reverse("steam", uri).as_deref(), Some("steam:100"));
+        assert_eq!(reverse("steam", "https://store.steampowered.com/"), None);
+        let uri = "https://foo.itch.io/foo-game";
+        assert_eq!(reverse("itch", uri).as_deref(), Some(uri));
+
+        let link = auto(uri);
+        assert_eq!(link.rule.unwrap().name, "itch");
+        assert_eq!(link.uri, uri);
+        let rewrite = link.rewrite.unwrap();
+        assert_eq!(rewrite.from, uri);
+        assert_eq!(rewrite.to, format!("{{ name: .itch, uri: {uri} }}"));
+
+        // custom links are normalized too
+        let uri = "http://store.steampowered.com/app/100";
+        let link = rules.build_link(hint("Steam", uri)).unwrap();
+        assert_eq!(link.rule.unwrap().name, "steam");
+        assert_eq!(link.uri, "https://store.steampowered.com/app/100");
+        assert_eq!(link.variables["id"], "100");
+        assert_eq!(link.rewrite.unwrap().to, "steam:100");
+
+        let (rule, item) = rules.stock_link_item(uri).unwrap().unwrap();
+        assert_eq!(rule.name, "steam");
+        assert_eq!(item.as_str(), Some("steam:100"));
+        assert!(auto("steam:100").rewrite.is_none());
+        assert!(rules.infer("https://example.com/").unwrap().is_none());
+    }
+
+    fn rule(toml: &str) -> StockLinkRule {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_compile() {
+        let mut ok = rule(
+            r#"
+regex = "^steam:(?P<id>.*)$"
+www_href = "https://store.steampowered.com/app/{{ id }}"
+label = { _ = "Steam {{ id }}", zh-cn = "蒸汽 {{ id }}" }
+reverse = ['^https://store\.steampowered\.com/app/(?P<id>[0-9]+)']
+canonical = "steam:{{ id }}"
+"#,
+        );
+        assert!(ok.compile().is_empty());
+        assert_eq!(ok.label[&LangId::ZhCn], "蒸汽 {{ id }}");
+
+        let mut bad = rule(
+            r#"
+inference = true
+regex = "^steam:(?P<id>.*)$"
+www_href = "https://store.steampowered.com/app/{{ app }}"
+label = { _ = "Steam", xx = "Steam" }
+reverse = ['^https://store\.steampowered\.com/app/(?P<app>[0-9]+)']
+canonical = "steam:{{ id"
+"#,
+        );
+        assert_eq!(
+            bad.compile(),
+            [
+                "www_href: No such resouce: Unknown template variable 'app'",
+                "invalid language key 'xx' of label",
+                "canonical, for reverse '^https://store\\.steampowered\\.com/app/(?P<app>[0-9]+)': \
+                 Invalid argument: Unclosed '{{' in template 'steam:{{ id'",
+            ]
+        );
+
+        let mut bad = rule("inference = true\nreverse = ['^https://(?P<host>.*)/']\n");
+        assert_eq!(
+            bad.compile(),
+            [
+                "regex is required for inference rule",
+                "canonical is required for reverse patterns",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_may_overlap() {
+        let regex = |re: &str| rule(&format!("regex = '{re}'"));
+
+        let steam = regex("^steam:(.*)$");
+        assert!(steam.may_overlap(&regex("^steam:app/(.*)$")));
+        assert!(regex("^steam:app/(.*)$").may_overlap(&steam));
+        assert!(steam.may_overlap(&regex("^steam:(.*)$")));
+        assert!(!steam.may_overlap(&regex("^twitter:(.*)$")));
+        assert!(!steam.may_overlap(&regex("^stea:(.*)$")));
+        // unanchored regexes may match anywhere
+        assert!(steam.may_overlap(&regex("twitter:(.*)$")));
+        assert!(steam.may_overlap(&rule("")));
+    }
+}
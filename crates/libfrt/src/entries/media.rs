@@ -1,9 +1,33 @@
 use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
 use std::path::PathBuf;
 
+use crate::i18n::L10nText;
 use crate::utils::is_remote::is_remote;
 
-use super::raw::{RawScreenshotItem, RawVideoSourceItem};
+use super::game::LANG_TAG_RE;
+use super::raw::{RawCaptionItem, RawHBoxItem, RawL10nText, RawScreenshotItem, RawVideoSourceItem};
+
+lazy_static! {
+    static ref YOUTUBE_ID_RE: Regex = Regex::new(r"^[A-Za-z0-9_-]{11}$").unwrap();
+    /// Video formats playable by browsers, optionally with a codecs parameter
+    static ref VIDEO_MIME_RE: Regex =
+        Regex::new(r#"^video/(mp4|webm|ogg)(\s*;\s*codecs="[^"]*")?$"#).unwrap();
+}
+
+/// Check whether `uri` is remote, or a file in the bundle
+fn check_file(uri: &str, bundle_path: Option<&PathBuf>) -> Result<bool> {
+    if is_remote(uri) {
+        return Ok(true);
+    }
+
+    match bundle_path {
+        Some(bp) if bp.join(uri).is_file() => Ok(false),
+        Some(bp) => crate::bail!(NotExist, "File not found: {}", bp.join(uri).display()),
+        None => crate::bail!(InvalidArgument, "Bundle path is required for non-remote files."),
+    }
+}
 
 #[derive(Debug)]
 pub enum ImageSource {
@@ -15,7 +39,8 @@ pub enum ImageSource {
 #[derive(Debug)]
 pub struct Image {
     pub source: ImageSource,
-    pub captain: Option<String>,
+    pub caption: L10nText,
+    pub alt: L10nText,
 
     /// Size of the image, width and height.
     /// Maybe unavailable for remote images.
@@ -27,7 +52,15 @@ pub struct Image {
 }
 
 impl Image {
-    pub fn new_bundled(name: String, captain: Option<String>, file_path: &PathBuf) -> Result<Self> {
+    /// URI as written in the source: a path in the bundle, a shared file
+    /// or a remote URL
+    pub fn uri(&self) -> &str {
+        match &self.source {
+            ImageSource::LocalShared(s) | ImageSource::Bundled(s) | ImageSource::Remote(s) => s,
+        }
+    }
+
+    pub fn new_bundled(name: String, file_path: &PathBuf) -> Result<Self> {
         let dim = imagesize::size(&file_path).map_err(|e| {
             crate::err!(
                 InvalidFileOrData,
@@ -39,37 +72,35 @@ impl Image {
 
         Ok(Image {
             source: ImageSource::Bundled(name),
-            captain,
+            caption: L10nText::default(),
+            alt: L10nText::default(),
             size: Some((dim.width, dim.height)),
             mtime: None,
         })
     }
 
-    pub fn new_remote(name: String, captain: Option<String>) -> Self {
+    pub fn new_remote(name: String) -> Self {
         Image {
             source: ImageSource::Remote(name),
-            captain,
+            caption: L10nText::default(),
+            alt: L10nText::default(),
             size: None,
             mtime: None,
         }
     }
 
-    pub fn from_str<S>(
-        src: S,
-        captain: Option<String>,
-        bundle_path: Option<&PathBuf>,
-    ) -> Result<Image>
+    pub fn from_str<S>(src: S, bundle_path: Option<&PathBuf>) -> Result<Image>
     where
         S: AsRef<str>,
     {
         if is_remote(&src) {
-            Ok(Self::new_remote(src.as_ref().to_owned(), captain))
+            Ok(Self::new_remote(src.as_ref().to_owned()))
         } else {
             match bundle_path {
                 Some(bp) => {
                     let file_path = bp.join(src.as_ref());
                     if file_path.is_file() {
-                        Image::new_bundled(src.as_ref().to_owned(), captain, &file_path)
+                        Image::new_bundled(src.as_ref().to_owned(), &file_path)
                     } else {
                         crate::bail!(
                             NotExist,
@@ -85,28 +116,89 @@ impl Image {
             }
         }
     }
+
+    fn from_raw(
+        uri: String,
+        caption: Option<RawL10nText>,
+        alt: Option<RawL10nText>,
+        bundle_path: Option<&PathBuf>,
+    ) -> Result<Image> {
+        let mut image = Image::from_str(uri, bundle_path)?;
+        if let Some(caption) = caption {
+            image.caption = caption.into_l10n()?;
+        }
+        if let Some(alt) = alt {
+            image.alt = alt.into_l10n()?;
+        }
+        Ok(image)
+    }
 }
 
 #[derive(Debug)]
 pub struct VideoSource {
     pub mime: String,
     pub uri: String,
+    pub remote: bool,
 }
 
-impl From<RawVideoSourceItem> for VideoSource {
-    fn from(value: RawVideoSourceItem) -> Self {
-        Self {
-            mime: value.mime,
-            uri: value.uri,
+impl VideoSource {
+    pub fn from_raw(raw: RawVideoSourceItem, bundle_path: Option<&PathBuf>) -> Result<Self> {
+        if !VIDEO_MIME_RE.is_match(&raw.mime) {
+            crate::bail!(
+                InvalidArgument,
+                "Unsupported video MIME type '{}' for '{}', expect video/mp4, video/webm or video/ogg",
+                raw.mime,
+                raw.uri
+            )
         }
+
+        Ok(Self {
+            remote: check_file(&raw.uri, bundle_path)?,
+            mime: raw.mime,
+            uri: raw.uri,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct VideoCaption {
+    /// Language of the captions, as lowercase language tag
+    pub lang: String,
+    pub label: Option<String>,
+    pub uri: String,
+    pub remote: bool,
+}
+
+impl VideoCaption {
+    pub fn from_raw(raw: RawCaptionItem, bundle_path: Option<&PathBuf>) -> Result<Self> {
+        if !LANG_TAG_RE.is_match(&raw.lang) {
+            crate::bail!(InvalidArgument, "Invalid language tag of captions: '{}'", raw.lang)
+        }
+
+        if !raw.uri.ends_with(".vtt") {
+            crate::bail!(InvalidArgument, "Captions must be a WebVTT file: '{}'", raw.uri)
+        }
+
+        Ok(Self {
+            lang: raw.lang.to_lowercase(),
+            label: raw.label,
+            remote: check_file(&raw.uri, bundle_path)?,
+            uri: raw.uri,
+        })
     }
 }
 
 #[derive(Debug)]
 pub enum Media {
     Image(Image),
+    /// Id of a YouTube video
     Youtube(String),
-    Video { sources: Vec<VideoSource> },
+    Video {
+        /// In order of preference
+        sources: Vec<VideoSource>,
+        poster: Option<Image>,
+        captions: Vec<VideoCaption>,
+    },
     HBox(Vec<Image>),
 }
 
@@ -119,20 +211,130 @@ impl From<Image> for Media {
 impl Media {
     pub fn from_raw(raw: RawScreenshotItem, bundle_path: Option<&PathBuf>) -> Result<Self> {
         match raw {
-            RawScreenshotItem::SimpleImage(uri) | RawScreenshotItem::Image { uri, .. } => {
-                Ok(Self::Image(Image::from_str(uri, None, bundle_path)?))
+            RawScreenshotItem::SimpleImage(uri) => Ok(Self::Image(Image::from_str(uri, bundle_path)?)),
+            RawScreenshotItem::Image {
+                uri, caption, alt, ..
+            } => Ok(Self::Image(Image::from_raw(uri, caption, alt, bundle_path)?)),
+            RawScreenshotItem::Youtube { youtube } => {
+                if !YOUTUBE_ID_RE.is_match(&youtube) {
+                    crate::bail!(InvalidArgument, "Invalid YouTube video id: '{}'", youtube)
+                }
+                Ok(Self::Youtube(youtube))
+            }
+            RawScreenshotItem::Video {
+                video,
+                poster,
+                captions,
+                ..
+            } => {
+                if video.is_empty() {
+                    crate::bail!(InvalidArgument, "Video requires at least one source")
+                }
+
+                Ok(Self::Video {
+                    sources: video
+                        .into_iter()
+                        .map(|raw| VideoSource::from_raw(raw, bundle_path))
+                        .collect::<Result<_>>()?,
+                    poster: match poster {
+                        Some(poster) => Some(Image::from_str(poster, bundle_path)?),
+                        None => None,
+                    },
+                    captions: captions
+                        .into_iter()
+                        .map(|raw| VideoCaption::from_raw(raw, bundle_path))
+                        .collect::<Result<_>>()?,
+                })
             }
-            RawScreenshotItem::Youtube { youtube } => Ok(Self::Youtube(youtube)),
-            RawScreenshotItem::Video { video, .. } => Ok(Self::Video {
-                sources: video.into_iter().map(|rvs| rvs.into()).collect(),
-            }),
             RawScreenshotItem::HBox { hbox, .. } => {
                 let mut result = Vec::new();
-                for image in hbox.into_iter() {
-                    result.push(Image::from_str(image, None, bundle_path)?);
+                for item in hbox.into_iter() {
+                    result.push(match item {
+                        RawHBoxItem::SimpleImage(uri) => Image::from_str(uri, bundle_path)?,
+                        RawHBoxItem::Image { uri, caption, alt } => {
+                            Image::from_raw(uri, caption, alt, bundle_path)?
+                        }
+                    });
                 }
                 Ok(Self::HBox(result))
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Media;
+    use anyhow::Result;
+
+    fn media(yaml: &str) -> Result<Media> {
+        Media::from_raw(serde_yaml::from_str(yaml)?, None)
+    }
+
+    fn error(yaml: &str) -> String {
+        media(yaml).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_youtube() {
+        assert!(
+            matches!(media("youtube: dQw4w9WgXcQ"), Ok(Media::Youtube(id)) if id == "dQw4w9WgXcQ")
+        );
+        assert!(error("youtube: dQw4w9WgXc").contains("Invalid YouTube video id"));
+        assert!(error("youtube: https://youtu.be/dQw4w9WgXcQ").contains("Invalid YouTube video id"));
+    }
+
+    #[test]
+    fn test_video() {
+        let video = |mime: &str| {
+            media(&format!(
+                "video:\n- mime: '{mime}'\n  uri: https://example.com/a"
+            ))
+        };
+
+        for mime in [
+            "video/mp4",
+            "video/webm",
+            "video/ogg",
+            r#"video/webm; codecs="vp9, opus""#,
+        ] {
+            assert!(video(mime).is_ok(), "{mime}");
+        }
+        for mime in [
+            "video/avi",
+            "audio/ogg",
+            "video/mp4; charset=utf-8",
+            "VIDEO/MP4",
+        ] {
+            let e = video(mime).unwrap_err().to_string();
+            assert!(e.contains("Unsupported video MIME type"), "{mime}");
+        }
+
+        assert!(error("video: []").contains("Video requires at least one source"));
+    }
+
+    #[test]
+    fn test_captions() {
+        let captions = |lang: &str, uri: &str| {
+            media(&format!(
+                "video:\n- mime: video/mp4\n  uri: https://example.com/a.mp4\n\
+                 captions:\n- lang: {lang}\n  uri: {uri}\n"
+            ))
+        };
+
+        match captions("zh-CN", "https://example.com/zh.vtt").unwrap() {
+            Media::Video { captions, .. } => {
+                assert_eq!(captions[0].lang, "zh-cn");
+                assert!(captions[0].remote);
+            }
+            _ => panic!("not a video"),
+        }
+
+        let e = captions("zh-CN", "https://example.com/zh.srt").unwrap_err();
+        assert!(e.to_string().contains("Captions must be a WebVTT file"));
+        let e = captions("chinese", "https://example.com/zh.vtt").unwrap_err();
+        assert!(e.to_string().contains("Invalid language tag of captions"));
+        let e = captions("en", "en.vtt").unwrap_err();
+        assert!(e.to_string().contains("Bundle path is required"));
+    }
+}
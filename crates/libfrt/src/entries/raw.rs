@@ -1,15 +1,19 @@
 use std::collections::HashMap;
 
+use anyhow::Result;
 use serde::Deserialize;
 
-use super::link::StockLinkRule;
+use super::game::{
+    ContentRating, GameStateKind, Monetization, Platform, RelationKind, ReleaseStatus,
+};
+use crate::i18n::{L10nText, LangId};
 
 #[derive(Deserialize, Debug)]
 pub struct RawStockConfig {
-    pub link: HashMap<String, StockLinkRule>
+    /// Link rules in file order, each one is a `StockLinkRule`
+    pub link: toml::value::Map<String, toml::Value>,
 }
 
-
 #[derive(Deserialize, Debug)]
 pub struct RawAuthorItem {
     pub name: String,
@@ -25,16 +29,64 @@ pub enum RawLinkItem {
     Custom {
         name: String,
         uri: String,
+        #[serde(default)]
+        patch: Option<RawPatch>,
     },
     Auto(String)
 }
 
+/// Metadata of a third-party localization patch link
+#[derive(Deserialize, Debug)]
+pub struct RawPatch {
+    pub lang: String,
+    pub author: Option<String>,
+    /// Translated percentage, 0 to 100
+    pub completeness: Option<u8>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct RawVideoSourceItem {
     pub mime: String,
     pub uri: String,
 }
 
+/// WebVTT caption track of a video
+#[derive(Deserialize, Debug)]
+pub struct RawCaptionItem {
+    pub lang: String,
+    pub uri: String,
+    pub label: Option<String>,
+}
+
+/// A text, or texts keyed by language tag with `_` for the default language
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum RawL10nText {
+    Plain(String),
+    L10n(HashMap<String, String>),
+}
+
+impl RawL10nText {
+    pub fn into_l10n(self) -> Result<L10nText> {
+        Ok(L10nText(match self {
+            RawL10nText::Plain(s) => HashMap::from([(LangId::default(), s)]),
+            RawL10nText::L10n(map) => {
+                let mut ret = HashMap::new();
+                for (key, s) in map.into_iter() {
+                    let lang = match key.as_str() {
+                        "_" => LangId::default(),
+                        key => LangId::from_tag(key).ok_or_else(|| {
+                            crate::err!(InvalidArgument, "Invalid language key: '{}'", key)
+                        })?,
+                    };
+                    ret.insert(lang, s);
+                }
+                ret
+            }
+        }))
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(untagged)]
 pub enum RawScreenshotItem {
@@ -43,6 +95,8 @@ pub enum RawScreenshotItem {
         #[serde(default)]
         sensitive: bool,
         uri: String,
+        caption: Option<RawL10nText>,
+        alt: Option<RawL10nText>,
     },
     Youtube {
         youtube: String,
@@ -51,14 +105,29 @@ pub enum RawScreenshotItem {
         #[serde(default)]
         sensitive: bool,
         video: Vec<RawVideoSourceItem>,
+        poster: Option<String>,
+        #[serde(default)]
+        captions: Vec<RawCaptionItem>,
     },
     HBox {
         #[serde(default)]
         sensitive: bool,
-        hbox: Vec<String>,
+        hbox: Vec<RawHBoxItem>,
     }
 }
 
+/// Image of a `hbox`, either a plain URI or with caption and alt text
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum RawHBoxItem {
+    SimpleImage (String),
+    Image {
+        uri: String,
+        caption: Option<RawL10nText>,
+        alt: Option<RawL10nText>,
+    },
+}
+
 impl RawScreenshotItem {
     pub fn is_sensitive(&self) -> bool {
         match self {
@@ -72,10 +141,18 @@ impl RawScreenshotItem {
     }
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum RawDate {
+    Year(u16),
+    Date(String),
+}
+
 #[derive(Deserialize, Debug)]
 pub struct RawGame {
     pub name: String,
 
+    #[serde(default)]
     pub description: String,
 
     #[serde(rename = "description-format")]
@@ -84,7 +161,36 @@ pub struct RawGame {
     #[serde(rename = "brief-description")]
     pub brief_description: Option<String>,
 
-    pub thumbnail: String,
+    pub thumbnail: Option<String>,
+
+    #[serde(default)]
+    pub rating: ContentRating,
+
+    #[serde(default)]
+    pub state: GameStateKind,
+
+    #[serde(rename = "state-reason")]
+    pub state_reason: Option<String>,
+
+    #[serde(rename = "redirect-to")]
+    pub redirect_to: Option<String>,
+
+    #[serde(default)]
+    pub platforms: Vec<Platform>,
+
+    #[serde(rename = "release-status")]
+    pub release_status: Option<ReleaseStatus>,
+
+    #[serde(rename = "release-date")]
+    pub release_date: Option<RawDate>,
+
+    #[serde(default)]
+    pub languages: Vec<String>,
+
+    pub monetization: Option<Monetization>,
+
+    #[serde(default)]
+    pub relations: HashMap<RelationKind, Vec<String>>,
 
     #[serde(default)]
     pub authors: Vec<RawAuthorItem>,
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Eq, PartialEq, Clone, Copy, Hash, Debug)]
@@ -16,12 +18,7 @@ impl Default for LangId {
 
 impl From<&str> for LangId {
     fn from(s: &str) -> Self {
-        match s {
-            "zh-cn" | "zh" => LangId::ZhCn,
-            "zh-tw" => LangId::ZhTw,
-            "ja-jp" | "ja" => LangId::JaJp,
-            _ => LangId::EnUs,
-        }
+        LangId::from_tag(s).unwrap_or_default()
     }
 }
 
@@ -36,6 +33,17 @@ impl<'de> Deserialize<'de> for LangId {
 }
 
 impl LangId {
+    /// Parse a language tag, `None` for unsupported languages
+    pub fn from_tag(s: &str) -> Option<Self> {
+        match s {
+            "en-us" | "en" => Some(LangId::EnUs),
+            "zh-cn" | "zh" => Some(LangId::ZhCn),
+            "zh-tw" => Some(LangId::ZhTw),
+            "ja-jp" | "ja" => Some(LangId::JaJp),
+            _ => None,
+        }
+    }
+
     pub fn tag(&self) -> (&'static str, &'static str, bool) {
         match self {
             LangId::EnUs => ("en", "us", true),
@@ -82,4 +90,21 @@ impl std::fmt::Display for LangId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.as_str())
     }
-}
\ No newline at end of file
+}
+
+/// Text with per-language variants, the default language is the fallback
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct L10nText(pub HashMap<LangId, String>);
+
+impl L10nText {
+    pub fn get(&self, lang: LangId) -> Option<&str> {
+        self.0
+            .get(&lang)
+            .or_else(|| self.0.get(&LangId::default()))
+            .map(|s| s.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.values().all(|s| s.trim().is_empty())
+    }
+}
@@ -0,0 +1,662 @@
+//! Migration of the old FGI game database, used by `frt import`.
+//!
+//! The old database keeps `games/<id>.yaml` and `assets/<id>/`. Each game is
+//! converted into a bundle directory with a `game.yaml` readable as
+//! `RawGame`, and the assets copied next to it. Whatever can not be
+//! converted is collected in the migration report instead of failing.
+
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Result;
+use serde_yaml::{Mapping, Value};
+
+use crate::entries::game::{Platform, LANG_TAG_RE};
+use crate::entries::link::LinkRuleManager;
+use crate::entries::raw::RawGame;
+use crate::utils::fs::copy_dir;
+
+#[derive(Debug)]
+pub struct MigrationNote {
+    pub game: String,
+    pub message: String,
+}
+
+impl fmt::Display for MigrationNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.game, self.message)
+    }
+}
+
+/// Keys of the current format, copied as they are
+const CURRENT_KEYS: &[&str] = &[
+    "rating",
+    "state",
+    "state-reason",
+    "redirect-to",
+    "platforms",
+    "release-status",
+    "release-date",
+    "languages",
+    "monetization",
+    "relations",
+];
+
+/// Old `misc` tags with a counterpart in the current format
+const MISC_TAGS: &[(&str, &str, &str)] = &[
+    ("freeware", "monetization", "free"),
+    ("work-in-progress", "release-status", "in-development"),
+    ("early-access", "release-status", "early-access"),
+    ("cancelled", "release-status", "cancelled"),
+];
+
+/// Old tag namespaces which have no counterpart in the current format
+const DROPPED_TAGS: &[&str] = &["type", "species", "fetish", "publish", "sys"];
+
+fn link_item(name: &str, uri: &str) -> Value {
+    let mut item = Mapping::new();
+    item.insert("name".into(), name.into());
+    item.insert("uri".into(), uri.into());
+    Value::Mapping(item)
+}
+
+/// Strings of a scalar or a sequence of scalars
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::Sequence(seq) => seq.iter().flat_map(strings).collect(),
+        Value::String(s) => vec![s.clone()],
+        Value::Number(n) => vec![n.to_string()],
+        Value::Bool(b) => vec![b.to_string()],
+        _ => Vec::new(),
+    }
+}
+
+/// Conversion of a single game
+struct Migration<'a> {
+    rules: &'a LinkRuleManager,
+    game: String,
+    notes: Vec<MigrationNote>,
+}
+
+impl Migration<'_> {
+    fn note(&mut self, message: String) {
+        self.notes.push(MigrationNote {
+            game: self.game.clone(),
+            message,
+        });
+    }
+
+    fn convert(&mut self, mut old: Mapping) -> Result<Mapping> {
+        let mut new = Mapping::new();
+
+        for key in ["name", "description", "description-format", "brief-description"] {
+            if let Some(value) = old.remove(key) {
+                new.insert(key.into(), value);
+            }
+        }
+
+        if let Some(value) = old.remove("thumbnail") {
+            new.insert("thumbnail".into(), value);
+        }
+
+        if old.remove("expunge").and_then(|v| v.as_bool()) == Some(true) {
+            new.insert("state".into(), "expunged".into());
+        }
+
+        if old.remove("sensitive_media").and_then(|v| v.as_bool()) == Some(true) {
+            new.insert("rating".into(), "adult".into());
+        }
+
+        for key in CURRENT_KEYS {
+            if let Some(value) = old.remove(*key) {
+                new.insert((*key).into(), value);
+            }
+        }
+
+        let mut tag_authors = Vec::new();
+        if let Some(tags) = old.remove("tags") {
+            match tags {
+                Value::Mapping(tags) => self.tags(tags, &mut new, &mut tag_authors),
+                _ => self.note("'tags' is not a mapping, dropped".to_owned()),
+            }
+        }
+
+        let authors = self.authors(old.remove("authors"), tag_authors);
+        if !authors.is_empty() {
+            new.insert("authors".into(), Value::Sequence(authors));
+        }
+
+        if let Some(links) = old.remove("links") {
+            let links = self.links(links)?;
+            if !links.is_empty() {
+                new.insert("links".into(), Value::Sequence(links));
+            }
+        }
+
+        if let Some(screenshots) = old.remove("screenshots") {
+            let screenshots = self.screenshots(screenshots);
+            if !screenshots.is_empty() {
+                new.insert("screenshots".into(), Value::Sequence(screenshots));
+            }
+        }
+
+        for (key, _) in old {
+            self.note(format!("unknown field '{}', dropped", strings(&key).join("")));
+        }
+
+        Ok(new)
+    }
+
+    fn tags(&mut self, tags: Mapping, new: &mut Mapping, authors: &mut Vec<String>) {
+        let mut platforms = Vec::new();
+        let mut languages = Vec::new();
+
+        for (namespace, values) in tags {
+            let namespace = strings(&namespace).join("");
+            let values = strings(&values);
+
+            match namespace.as_str() {
+                "platform" => {
+                    for value in values {
+                        let platform: Result<Platform, _> =
+                            serde_yaml::from_value(Value::from(value.as_str()));
+                        match platform {
+                            Ok(_) => platforms.push(Value::from(value)),
+                            Err(_) => self.note(format!("unknown platform '{}', dropped", value)),
+                        }
+                    }
+                }
+                "lang" => {
+                    for value in values {
+                        let lang = value.to_lowercase();
+                        match LANG_TAG_RE.is_match(&lang) {
+                            true => languages.push(Value::from(lang)),
+                            false => self.note(format!("invalid language '{}', dropped", value)),
+                        }
+                    }
+                }
+                "author" => authors.extend(values),
+                "misc" => {
+                    for value in values {
+                        match MISC_TAGS.iter().find(|(tag, ..)| *tag == value) {
+                            Some((_, key, v)) if !new.contains_key(*key) => {
+                                new.insert((*key).into(), (*v).into());
+                            }
+                            Some((_, key, _)) => self.note(format!(
+                                "tag 'misc:{}' conflicts with '{}', dropped",
+                                value, key
+                            )),
+                            None => self.note(format!("tag 'misc:{}' has no counterpart, dropped", value)),
+                        }
+                    }
+                }
+                ns if DROPPED_TAGS.contains(&ns) => {
+                    if !values.is_empty() {
+                        self.note(format!(
+                            "tags of '{}' have no counterpart, dropped: {}",
+                            ns,
+                            values.join(", ")
+                        ));
+                    }
+                }
+                ns => self.note(format!("unknown tag namespace '{}', dropped", ns)),
+            }
+        }
+
+        if !platforms.is_empty() && !new.contains_key("platforms") {
+            new.insert("platforms".into(), Value::Sequence(platforms));
+        }
+        if !languages.is_empty() && !new.contains_key("languages") {
+            new.insert("languages".into(), Value::Sequence(languages));
+        }
+    }
+
+    fn authors(&mut self, old: Option<Value>, tag_authors: Vec<String>) -> Vec<Value> {
+        let mut ret = Vec::new();
+        let mut names = Vec::new();
+
+        let items = match old {
+            Some(Value::Sequence(items)) => items,
+            Some(_) => {
+                self.note("'authors' is not a list, dropped".to_owned());
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+
+        for item in items {
+            let Value::Mapping(mut item) = item else {
+                self.note(format!("author '{}' is not a mapping, dropped", strings(&item).join("")));
+                continue;
+            };
+
+            let Some(name) = item.remove("name") else {
+                self.note("author without name, dropped".to_owned());
+                continue;
+            };
+
+            let mut author = Mapping::new();
+            names.push(strings(&name).join(""));
+            author.insert("name".into(), name);
+
+            let roles = item.remove("role").map(|r| strings(&r)).unwrap_or_default();
+            author.insert(
+                "role".into(),
+                Value::Sequence(roles.into_iter().map(Value::from).collect()),
+            );
+
+            if let Some(standalone) = item.remove("standalone") {
+                author.insert("standalone".into(), standalone);
+            }
+
+            for (key, _) in item {
+                self.note(format!(
+                    "unknown field '{}' of author '{}', dropped",
+                    strings(&key).join(""),
+                    names.last().unwrap()
+                ));
+            }
+
+            ret.push(Value::Mapping(author));
+        }
+
+        for name in tag_authors {
+            if !names.contains(&name) {
+                let mut author = Mapping::new();
+                author.insert("name".into(), name.as_str().into());
+                author.insert("role".into(), Value::Sequence(Vec::new()));
+                ret.push(Value::Mapping(author));
+                names.push(name);
+            }
+        }
+
+        ret
+    }
+
+    fn links(&mut self, links: Value) -> Result<Vec<Value>> {
+        let Value::Sequence(links) = links else {
+            self.note("'links' is not a list, dropped".to_owned());
+            return Ok(Vec::new());
+        };
+
+        let mut ret = Vec::new();
+
+        for link in links {
+            let mut item = match link {
+                Value::Mapping(item) => item,
+                Value::String(uri) => {
+                    ret.push(self.auto_link(uri)?);
+                    continue;
+                }
+                link => {
+                    self.note(format!("invalid link '{:?}', dropped", link));
+                    continue;
+                }
+            };
+
+            let name = item.remove("name").map(|v| strings(&v).join(""));
+            let uri = item.remove("uri").map(|v| strings(&v).join(""));
+            let patch = item.remove("patch");
+            item.remove("icon");
+
+            let (Some(name), Some(uri)) = (name, uri) else {
+                self.note("link without name or uri, dropped".to_owned());
+                continue;
+            };
+
+            for (key, _) in item {
+                self.note(format!(
+                    "unknown field '{}' of link '{}', dropped",
+                    strings(&key).join(""),
+                    name
+                ));
+            }
+
+            let mut converted = match name.strip_prefix('.') {
+                Some(rule) => self.hint_link(rule, &uri)?,
+                None => link_item(&name, &uri),
+            };
+
+            if let (Some(patch), Value::Mapping(item)) = (patch, &mut converted) {
+                item.insert("patch".into(), patch);
+            }
+
+            ret.push(converted);
+        }
+
+        Ok(ret)
+    }
+
+    /// Link written without name, which must be inferred
+    fn auto_link(&mut self, uri: String) -> Result<Value> {
+        Ok(match self.rules.infer(&uri)? {
+            Some((rule, normalized)) => {
+                if normalized != uri {
+                    self.note(format!("link '{}' normalized to '{}'", uri, normalized));
+                }
+                rule.link_item(&normalized)
+            }
+            None => {
+                self.note(format!("no rule recognizes link '{}', converted to custom link", uri));
+                link_item(&uri, &uri)
+            }
+        })
+    }
+
+    /// Link with a `.rule` hint, rewritten if the rule is unknown or does
+    /// not accept the URI
+    fn hint_link(&mut self, rule_name: &str, uri: &str) -> Result<Value> {
+        if let Some(item) = self.hinted(rule_name, uri)? {
+            return Ok(item);
+        }
+
+        if let Some(suggestion) = self.rules.suggest_rule(rule_name, Some(uri)) {
+            if let Some(item) = self.hinted(&suggestion, uri)? {
+                self.note(format!(
+                    "link rule '.{}' rewritten to '.{}' for '{}'",
+                    rule_name, suggestion, uri
+                ));
+                return Ok(item);
+            }
+        }
+
+        if let Some((_, item)) = self.rules.stock_link_item(uri)? {
+            self.note(format!(
+                "link rule '.{}' does not accept '{}', left to inference",
+                rule_name, uri
+            ));
+            return Ok(item);
+        }
+
+        self.note(format!(
+            "link rule '.{}' can not be applied to '{}', converted to custom link",
+            rule_name, uri
+        ));
+        Ok(link_item(rule_name, uri))
+    }
+
+    /// Link hinted with `rule_name`, with full URLs recognized by a reverse
+    /// pattern normalized. `None` if the rule does not accept `uri`.
+    fn hinted(&mut self, rule_name: &str, uri: &str) -> Result<Option<Value>> {
+        let Some(rule) = self.rules.rules.get(rule_name) else {
+            return Ok(None);
+        };
+
+        let hint = format!(".{}", rule_name);
+        if rule.match_uri(uri) {
+            return Ok(Some(link_item(&hint, uri)));
+        }
+
+        let Some(normalized) = rule.reverse_uri(uri)? else {
+            return Ok(None);
+        };
+
+        self.note(format!("link '{}' normalized to '{}'", uri, normalized));
+        Ok(Some(link_item(&hint, &normalized)))
+    }
+
+    fn screenshots(&mut self, screenshots: Value) -> Vec<Value> {
+        let Value::Sequence(screenshots) = screenshots else {
+            self.note("'screenshots' is not a list, dropped".to_owned());
+            return Vec::new();
+        };
+
+        let mut ret = Vec::new();
+
+        for (i, item) in screenshots.into_iter().enumerate() {
+            let mut item = match item {
+                Value::Mapping(item) => item,
+                item @ Value::String(_) => {
+                    ret.push(item);
+                    continue;
+                }
+                _ => {
+                    self.note(format!("screenshot #{} is invalid, dropped", i + 1));
+                    continue;
+                }
+            };
+
+            let kind = match item.remove("type") {
+                Some(kind) => strings(&kind).join(""),
+                // already in the current format
+                None => {
+                    ret.push(Value::Mapping(item));
+                    continue;
+                }
+            };
+
+            let mut new = Mapping::new();
+            if let Some(sensitive) = item.remove("sensitive") {
+                new.insert("sensitive".into(), sensitive);
+            }
+
+            match kind.as_str() {
+                "image" => match item.remove("uri") {
+                    Some(uri) => {
+                        new.insert("uri".into(), uri);
+                    }
+                    None => {
+                        self.note(format!("image screenshot #{} without uri, dropped", i + 1));
+                        continue;
+                    }
+                },
+                "youtube" => {
+                    let uri = item.remove("uri").map(|v| strings(&v).join("")).unwrap_or_default();
+                    let id = uri.strip_prefix("youtube:").unwrap_or(&uri);
+                    if id.is_empty() {
+                        self.note(format!("youtube screenshot #{} without uri, dropped", i + 1));
+                        continue;
+                    }
+                    new.remove("sensitive");
+                    new.insert("youtube".into(), id.into());
+                }
+                "video" => match item.remove("src") {
+                    Some(src) => {
+                        new.insert("video".into(), src);
+                    }
+                    None => {
+                        self.note(format!("video screenshot #{} without src, dropped", i + 1));
+                        continue;
+                    }
+                },
+                kind => {
+                    self.note(format!(
+                        "screenshot #{} of unknown type '{}', dropped",
+                        i + 1,
+                        kind
+                    ));
+                    continue;
+                }
+            }
+
+            for (key, _) in item {
+                self.note(format!(
+                    "unknown field '{}' of screenshot #{}, dropped",
+                    strings(&key).join(""),
+                    i + 1
+                ));
+            }
+
+            ret.push(Value::Mapping(new));
+        }
+
+        ret
+    }
+}
+
+/// Convert the content of an old `games/<id>.yaml` into a `game.yaml`
+pub fn convert_game(
+    rules: &LinkRuleManager,
+    id: &str,
+    content: &str,
+) -> Result<(String, Vec<MigrationNote>)> {
+    let Value::Mapping(old) = serde_yaml::from_str(content)? else {
+        crate::bail!(InvalidFileOrData, "Game '{}' is not a mapping", id)
+    };
+
+    let mut migration = Migration {
+        rules,
+        game: id.to_owned(),
+        notes: Vec::new(),
+    };
+
+    let new = Value::Mapping(migration.convert(old)?);
+
+    if let Err(e) = serde_yaml::from_value::<RawGame>(new.clone()) {
+        migration.note(format!("converted game.yaml is still invalid: {}", e));
+    }
+
+    Ok((serde_yaml::to_string(&new)?, migration.notes))
+}
+
+/// Import every game of the old database at `src` into bundles in `dst`.
+/// Existing bundles are kept unless `overwrite` is set.
+pub fn import_games(
+    rules: &LinkRuleManager,
+    src: &Path,
+    dst: &Path,
+    overwrite: bool,
+) -> Result<Vec<MigrationNote>> {
+    let mut files: Vec<_> = std::fs::read_dir(src.join("games"))?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    files.retain(|p| p.is_file() && p.extension().is_some_and(|e| e == "yaml"));
+    files.sort_unstable();
+
+    let mut notes = Vec::new();
+
+    for file in files {
+        let id = file
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| crate::err!(Other, "Can not parse file name: {}", file.display()))?;
+
+        let bundle = dst.join(id);
+        if bundle.exists() && !overwrite {
+            notes.push(MigrationNote {
+                game: id.to_owned(),
+                message: format!("bundle '{}' exists, skipped", bundle.display()),
+            });
+            continue;
+        }
+
+        info!("Importing game: {id}");
+
+        let converted = std::fs::read_to_string(&file)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| convert_game(rules, id, &content));
+        let yaml = match converted {
+            Ok((yaml, game_notes)) => {
+                notes.extend(game_notes);
+                yaml
+            }
+            Err(e) => {
+                notes.push(MigrationNote {
+                    game: id.to_owned(),
+                    message: format!("can not be converted, skipped: {}", e),
+                });
+                continue;
+            }
+        };
+
+        let assets = src.join("assets").join(id);
+        match assets.is_dir() {
+            true => copy_dir(&assets, &bundle)?,
+            false => std::fs::create_dir_all(&bundle)?,
+        }
+
+        std::fs::write(bundle.join("game.yaml"), yaml)?;
+    }
+
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entries::link::LinkRuleManager;
+    use crate::testing::{Fixture, STOCK};
+    use crate::ContextData;
+
+    fn rules() -> LinkRuleManager {
+        let mut data = ContextData::default();
+        data.load_stock_str(STOCK, "stock.toml").unwrap();
+        data.link_rules
+    }
+
+    #[test]
+    fn test_convert_game() {
+        let old = "name: Foo\n\
+                   tags:\n  platform: [linux, dos]\n  misc: [freeware]\n  species: [fox]\n\
+                   screenshots:\n  - type: youtube\n    uri: youtube:dQw4w9WgXcQ\n\
+                   expunge: true\n";
+
+        let (yaml, notes) = super::convert_game(&rules(), "foo", old).unwrap();
+        assert_eq!(
+            yaml,
+            "name: Foo\nstate: expunged\nmonetization: free\nplatforms:\n- linux\n\
+             screenshots:\n- youtube: dQw4w9WgXcQ\n"
+        );
+
+        let notes: Vec<_> = notes.iter().map(|n| n.message.as_str()).collect();
+        assert_eq!(
+            notes,
+            [
+                "unknown platform 'dos', dropped",
+                "tags of 'species' have no counterpart, dropped: fox"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_links() {
+        let old = "name: Foo\nlinks:\n\
+                   - { name: .stema, uri: 'steam:100' }\n\
+                   - { name: .steam, uri: 'https://store.steampowered.com/app/200/Foo/' }\n\
+                   - https://store.steampowered.com/app/300\n\
+                   - { name: .website, uri: 'https://example.com/' }\n\
+                   - { name: .nosuch, uri: 'nosuch:foo' }\n\
+                   - https://example.org/\n";
+
+        let (yaml, notes) = super::convert_game(&rules(), "foo", old).unwrap();
+        assert_eq!(
+            yaml,
+            "name: Foo\nlinks:\n\
+             - name: .steam\n  uri: steam:100\n\
+             - name: .steam\n  uri: steam:200\n\
+             - steam:300\n\
+             - name: .website\n  uri: https://example.com/\n\
+             - name: nosuch\n  uri: nosuch:foo\n\
+             - name: https://example.org/\n  uri: https://example.org/\n"
+        );
+
+        let notes: Vec<_> = notes.iter().map(|n| n.message.as_str()).collect();
+        assert_eq!(
+            notes,
+            [
+                "link rule '.stema' rewritten to '.steam' for 'steam:100'",
+                "link 'https://store.steampowered.com/app/200/Foo/' normalized to 'steam:200'",
+                "link 'https://store.steampowered.com/app/300' normalized to 'steam:300'",
+                "link rule '.nosuch' can not be applied to 'nosuch:foo', converted to custom link",
+                "no rule recognizes link 'https://example.org/', converted to custom link",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_games_skips_invalid() {
+        // removes the directories when dropped
+        let fixture = Fixture::new("import");
+        let (src, dst) = (fixture.root.join("old"), fixture.root.join("imported"));
+        std::fs::create_dir_all(src.join("games")).unwrap();
+        std::fs::write(src.join("games/bad.yaml"), "- not a mapping\n").unwrap();
+        std::fs::write(src.join("games/good.yaml"), "name: Good\n").unwrap();
+
+        let notes = super::import_games(&fixture.data.link_rules, &src, &dst, false).unwrap();
+
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].game, "bad");
+        assert!(notes[0].message.starts_with("can not be converted, skipped"));
+        assert!(!dst.join("bad").exists());
+        assert!(dst.join("good/game.yaml").is_file());
+    }
+}
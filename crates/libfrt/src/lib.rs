@@ -2,22 +2,29 @@ pub mod backend;
 pub mod entries;
 pub mod error;
 pub mod i18n;
+pub mod import;
+pub mod linkcheck;
+pub mod lint;
 pub mod profile;
+pub mod scaffold;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod utils;
 
 #[macro_use]
 extern crate log;
 
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use std::rc::Rc;
 
-use backend::{Backend, BackendArguments};
-use entries::link::LinkRuleManager;
+use backend::{BackendArguments, BackendInstance};
+use entries::link::{LinkRuleManager, StockLinkRule};
 use entries::raw::RawStockConfig;
-use entries::{author::Author, game::Game};
+use entries::author::Author;
+use entries::game::{Game, GameState, Relation};
 use error::{Error, ErrorKind};
 use i18n::LangId;
 use profile::Profile;
@@ -39,6 +46,157 @@ impl ContextData {
         Ok(())
     }
 
+    /// Drop sensitive games, redirects and relations to them, and sensitive
+    /// medias of the remaining games.
+    pub fn exclude_sensitive(&mut self) {
+        let count = self.games.len();
+        self.games.retain(|_, game| !game.is_sensitive());
+
+        let remaining: HashSet<String> = self.games.keys().cloned().collect();
+        self.games.retain(|_, game| match &game.state {
+            GameState::Redirected { target } => remaining.contains(target),
+            _ => true,
+        });
+
+        info!("{} sensitive games excluded", count - self.games.len());
+
+        for game in self.games.values_mut() {
+            match Rc::get_mut(game) {
+                Some(game) => {
+                    game.medias.retain(|m| !m.sensitive);
+                    game.relations.retain(|r| remaining.contains(&r.target));
+                }
+                None => warn!("Game '{}' is shared, can not exclude sensitive medias", game.id),
+            }
+        }
+    }
+
+    /// Validate references between games, once all game bundles are loaded
+    pub fn post_load_games(&mut self) -> Result<()> {
+        for game in self.games.values() {
+            if let GameState::Redirected { target } = &game.state {
+                match self.games.get(target) {
+                    Some(t) if t.state.is_tombstone() => crate::bail!(
+                        InvalidBundle,
+                        "Game '{}' redirects to '{}', which is {} too",
+                        game.id,
+                        target,
+                        t.state.as_str()
+                    ),
+                    Some(_) => {}
+                    None => crate::bail!(
+                        NotExist,
+                        "Game '{}' redirects to non-existent game '{}'",
+                        game.id,
+                        target
+                    ),
+                }
+            }
+        }
+
+        self.resolve_relations()
+    }
+
+    /// Check relationships between games and make them bidirectional
+    fn resolve_relations(&mut self) -> Result<()> {
+        let mut errors = 0;
+        let mut resolved: HashMap<String, Vec<Relation>> = HashMap::new();
+
+        for game in self.games.values() {
+            if game.state.is_tombstone() {
+                continue;
+            }
+
+            for relation in game.relations.iter() {
+                let mut target = relation.target.clone();
+
+                match self.games.get(&target).map(|t| &t.state) {
+                    Some(GameState::Redirected { target: new_target }) => {
+                        warn!(
+                            "Game '{}': {} '{}' is redirected to '{}', please update the id",
+                            game.id,
+                            relation.kind.as_str(),
+                            target,
+                            new_target
+                        );
+                        target = new_target.clone();
+                    }
+                    Some(GameState::Expunged { .. }) => {
+                        error!(
+                            "Game '{}': {} '{}' is expunged",
+                            game.id,
+                            relation.kind.as_str(),
+                            target
+                        );
+                        errors += 1;
+                        continue;
+                    }
+                    Some(_) => {}
+                    None => {
+                        error!(
+                            "Game '{}': {} '{}' does not exist",
+                            game.id,
+                            relation.kind.as_str(),
+                            target
+                        );
+                        errors += 1;
+                        continue;
+                    }
+                }
+
+                if target == game.id {
+                    error!("Game '{}' can not relate to itself", game.id);
+                    errors += 1;
+                    continue;
+                }
+
+                resolved
+                    .entry(game.id.clone())
+                    .or_default()
+                    .push(Relation {
+                        kind: relation.kind,
+                        target: target.clone(),
+                    });
+                resolved.entry(target).or_default().push(Relation {
+                    kind: relation.kind.inverse(),
+                    target: game.id.clone(),
+                });
+            }
+        }
+
+        for (id, relations) in resolved.iter_mut() {
+            relations.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.target.cmp(&b.target)));
+            relations.dedup();
+
+            for r in relations.iter() {
+                if r.kind < r.kind.inverse()
+                    && relations.iter().any(|o| o.target == r.target && o.kind == r.kind.inverse())
+                {
+                    error!(
+                        "Game '{}': '{}' is both {} and {}",
+                        id,
+                        r.target,
+                        r.kind.as_str(),
+                        r.kind.inverse().as_str()
+                    );
+                    errors += 1;
+                }
+            }
+        }
+
+        if errors > 0 {
+            crate::bail!(InvalidBundle, "{} invalid game relations found", errors)
+        }
+
+        for (id, relations) in resolved.into_iter() {
+            if let Some(game) = self.games.get_mut(&id).and_then(Rc::get_mut) {
+                game.relations = relations;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn build_author(&self, path: &Path) -> Result<Author> {
         todo!()
     }
@@ -49,24 +207,54 @@ impl ContextData {
 
     pub fn load_stock(&mut self, file: &Path) -> Result<()> {
         let content = std::fs::read_to_string(file)?;
-        let mut stock_config: RawStockConfig = toml::from_str(content.as_str())?;
+        self.load_stock_str(&content, &file.display().to_string())
+    }
+
+    /// Add the link rules of a stock config, `source` names it in errors
+    pub fn load_stock_str(&mut self, content: &str, source: &str) -> Result<()> {
+        let stock_config: RawStockConfig = toml::from_str(content)?;
+
+        let mut errors = 0;
+        for (rule_name, value) in stock_config.link {
+            let mut rule: StockLinkRule = match value.try_into() {
+                Ok(rule) => rule,
+                Err(e) => {
+                    error!("StockLink '{}': {}", rule_name, e);
+                    errors += 1;
+                    continue;
+                }
+            };
 
-        for (rule_name, mut rule) in stock_config.link.drain() {
             rule.name = rule_name;
-            self.link_rules.add_rule(rule)?;
+            if self.link_rules.add_rule(rule).is_err() {
+                errors += 1;
+            }
+        }
+
+        if errors > 0 {
+            crate::bail!(
+                InvalidArgument,
+                "{} invalid link rules in '{}'",
+                errors,
+                source
+            )
         }
 
         Ok(())
     }
 
     pub fn load_ui(&mut self, file: &Path) -> Result<()> {
-        let content = std::fs::read_to_string(file)?;
+        self.load_ui_str(&std::fs::read_to_string(file)?)
+    }
+
+    /// Merge a ui config into the ones loaded before
+    pub fn load_ui_str(&mut self, content: &str) -> Result<()> {
         self.ui
             .entry(LangId::default())
             .or_insert(toml::from_str("")?);
         utils::toml::merge(
             self.ui.get_mut(&LangId::default()).unwrap(),
-            toml::from_str(content.as_str())?,
+            toml::from_str(content)?,
         );
         Ok(())
     }
@@ -96,18 +284,185 @@ impl ContextData {
 
         Ok(())
     }
+
+    /// Ui config value of `lang` by a dotted key such as `main.title`
+    pub fn ui_raw(&self, lang: LangId, k: &str) -> Option<&toml::Value> {
+        let mut v = self.ui.get(&lang);
+
+        for i in k.split('.') {
+            v = match v {
+                Some(toml::Value::Table(t)) => t.get(i),
+                _ => None,
+            };
+        }
+
+        v
+    }
+
+    /// Ui string of `lang`, fallback to empty string with a warning
+    pub fn ui(&self, lang: LangId, k: &str) -> &str {
+        match self.ui_raw(lang, k) {
+            Some(toml::Value::String(s)) => s.as_str(),
+            Some(_) => {
+                warn!(
+                    "'{}' ui config is not a valid string type, fallback to empty string",
+                    k
+                );
+                ""
+            }
+            None => {
+                warn!("'{}' was not found in ui config, fallback to empty string", k);
+                ""
+            }
+        }
+    }
+
+    /// Languages with a ui config and the default language, sorted by tag
+    pub fn langs(&self) -> Vec<LangId> {
+        let mut langs: Vec<LangId> = self.ui.keys().copied().collect();
+        if !langs.contains(&LangId::default()) {
+            langs.push(LangId::default());
+        }
+        langs.sort_unstable_by_key(|l| l.as_str());
+        langs
+    }
+
+    /// Games for backends exporting the catalog as data, sorted by id.
+    ///
+    /// Only listed games, unless `include_unlisted` is set: then also
+    /// delisted games, and tombstones of expunged and redirected games.
+    pub fn exported_games(&self, include_unlisted: bool) -> Vec<&Game> {
+        let mut games: Vec<&Game> = self
+            .games
+            .values()
+            .filter(|g| include_unlisted || g.state.is_listed())
+            .map(|g| g.as_ref())
+            .collect();
+        games.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        games
+    }
+
+    /// Localized name of the game `id`, fallback to the id itself
+    pub fn game_name<'a>(&'a self, id: &'a str, lang: LangId) -> &'a str {
+        self.games
+            .get(id)
+            .map(|g| g.localized_name(lang))
+            .unwrap_or(id)
+    }
+
+    /// Games of each facet as `(namespace, value)`, sorted by id.
+    /// See `exported_games` for `include_unlisted`.
+    pub fn facets_index(&self, include_unlisted: bool) -> BTreeMap<(String, String), Vec<&Game>> {
+        let mut index: BTreeMap<(String, String), Vec<&Game>> = BTreeMap::new();
+        for game in self.exported_games(include_unlisted) {
+            for facet in game.facets() {
+                if let Some((namespace, value)) = facet.split_once(':') {
+                    let games = index
+                        .entry((namespace.to_owned(), value.to_owned()))
+                        .or_default();
+                    if games.last().is_none_or(|g| g.id != game.id) {
+                        games.push(game);
+                    }
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Localized label of a facet value, fallback to the value itself
+    pub fn facet_label(&self, lang: LangId, namespace: &str, value: &str) -> String {
+        // playable languages share labels with game languages
+        let namespace = match namespace {
+            "playable" => "language",
+            namespace => namespace,
+        };
+
+        match self.ui_raw(lang, &format!("facets.{}.{}", namespace, value)) {
+            Some(toml::Value::String(s)) => s.clone(),
+            _ => value.to_owned(),
+        }
+    }
+}
+
+/// Counts of loaded entries, for backend-less validation runs
+#[derive(Default, Debug)]
+pub struct ContextStats {
+    pub games: usize,
+    /// Number of games in each state, ordered by state name
+    pub games_by_state: Vec<(&'static str, usize)>,
+    pub sensitive_games: usize,
+    pub stock_links: usize,
+    pub custom_links: usize,
+    pub medias: usize,
+    pub authors: usize,
+    pub link_rules: usize,
+    pub languages: usize,
+}
+
+impl std::fmt::Display for ContextStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Games: {}", self.games)?;
+        for (state, count) in self.games_by_state.iter() {
+            writeln!(f, "  {}: {}", state, count)?;
+        }
+        writeln!(f, "  sensitive: {}", self.sensitive_games)?;
+        writeln!(
+            f,
+            "Links: {} ({} stock, {} custom)",
+            self.stock_links + self.custom_links,
+            self.stock_links,
+            self.custom_links
+        )?;
+        writeln!(f, "Medias: {}", self.medias)?;
+        writeln!(f, "Authors: {}", self.authors)?;
+        writeln!(f, "Stock link rules: {}", self.link_rules)?;
+        writeln!(f, "UI languages: {}", self.languages)
+    }
+}
+
+impl ContextData {
+    pub fn stats(&self) -> ContextStats {
+        let mut stats = ContextStats {
+            games: self.games.len(),
+            authors: self.authors.len(),
+            link_rules: self.link_rules.rules.len(),
+            languages: self.ui.len(),
+            ..Default::default()
+        };
+
+        let mut states: HashMap<&'static str, usize> = HashMap::new();
+        for game in self.games.values() {
+            *states.entry(game.state.as_str()).or_default() += 1;
+
+            if game.is_sensitive() {
+                stats.sensitive_games += 1;
+            }
+
+            let stock = game.links.iter().filter(|l| l.rule.is_some()).count();
+            stats.stock_links += stock;
+            stats.custom_links += game.links.len() - stock;
+            stats.medias += game.medias.len();
+        }
+
+        stats.games_by_state = states.into_iter().collect();
+        stats.games_by_state.sort_unstable();
+
+        stats
+    }
 }
 
 pub struct Context {
     pub profile: Profile,
 
-    pub(crate) backend: Option<Box<dyn Backend>>,
+    /// Backends in the order they render, sharing the loaded data
+    pub(crate) backends: Vec<BackendInstance>,
 
     pub(crate) data: ContextData,
 }
 
 impl Context {
-    pub fn new(mut profile: Profile, backend: Option<Box<dyn Backend>>) -> Result<Self> {
+    pub fn new(mut profile: Profile, backends: Vec<BackendInstance>) -> Result<Self> {
         info!("Context initializing");
 
         profile.backends.clear();
@@ -115,12 +470,16 @@ impl Context {
         Ok(Self {
             profile: profile,
 
-            backend: backend,
+            backends,
 
             data: ContextData::default(),
         })
     }
 
+    pub fn data(&self) -> &ContextData {
+        &self.data
+    }
+
     pub fn load_games(&mut self) -> Result<()> {
         for i in self.profile.path_games.iter() {
             info!("Loading game dir: {i}");
@@ -131,6 +490,26 @@ impl Context {
                     self.data.load_game(&path)?;
                 }
             }
+
+            if self.profile.git_timestamps {
+                match utils::git::entry_timestamps(i) {
+                    Ok(timestamps) => {
+                        for (id, (added, updated)) in timestamps {
+                            if let Some(game) = self.data.games.get_mut(&id).and_then(Rc::get_mut) {
+                                game.added = Some(added);
+                                game.updated = Some(updated);
+                            }
+                        }
+                    }
+                    Err(e) => warn!("Can not read git timestamps, fallback to file system: {e}"),
+                }
+            }
+        }
+
+        self.data.post_load_games()?;
+
+        if self.profile.exclude_sensitive {
+            self.data.exclude_sensitive();
         }
 
         info!("{} games Loaded", self.data.games.len());
@@ -138,6 +517,11 @@ impl Context {
         Ok(())
     }
 
+    /// Load a single game bundle, without checking references to other games
+    pub fn load_game_bundle(&mut self, path: &Path) -> Result<()> {
+        self.data.load_game(path)
+    }
+
     pub fn load_authors(&mut self) -> Result<()> {
         for i in self.profile.path_authors.iter() {
             info!("Loading author dir: {i}");
@@ -156,6 +540,8 @@ impl Context {
     }
 
     pub fn load_config(&mut self) -> Result<()> {
+        self.data.link_rules.hint_policy = self.profile.stock_link_hints;
+
         for i in &self.profile.stock_config {
             info!("Loading stock config '{i}'");
             self.data.load_stock(Path::new(i))?;
@@ -184,21 +570,238 @@ impl Context {
         Ok(())
     }
 
-    pub fn resync_backend(&mut self, args: &BackendArguments) -> Result<()> {
-        self.backend
-            .as_mut()
-            .unwrap()
-            .resync(&self.profile, &mut self.data, args)?;
+    /// Whether any backend is attached, otherwise the context only loads
+    /// and validates sources
+    pub fn has_backend(&self) -> bool {
+        !self.backends.is_empty()
+    }
+
+    pub fn resync_backends(&mut self) -> Result<()> {
+        for instance in self.backends.iter_mut() {
+            info!("Re-syncing backend '{}'", instance.name);
+            instance
+                .backend
+                .resync(&self.profile, &mut self.data, &instance.args)?;
+        }
         Ok(())
     }
 
-    pub fn invoke_backend(&self) -> Result<BackendArguments> {
-        let result = self
-            .backend
-            .as_ref()
-            .unwrap()
-            .render(&self.profile, &self.data)?;
-        info!("Render done");
-        Ok(result)
+    /// Render with every backend in order, returns their results
+    pub fn invoke_backends(&self) -> Result<Vec<BackendArguments>> {
+        if self.backends.is_empty() {
+            info!("No backend, render skipped");
+        }
+
+        let mut results = Vec::new();
+        for instance in self.backends.iter() {
+            info!("Render starting, backend: {}", instance.name);
+            results.push(instance.backend.render(&self.profile, &self.data)?);
+            info!("Render done, backend: {}", instance.name);
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    use anyhow::Result;
+
+    use crate::entries::game::Game;
+    use crate::testing::Fixture;
+    use crate::ContextData;
+
+    /// Games with a remote thumbnail and the given extra YAML, references
+    /// between them resolved
+    fn load(games: &[(&str, &str)]) -> Result<ContextData> {
+        let mut data = ContextData::default();
+        for (id, yaml) in games {
+            let yaml = format!("name: {id}\nthumbnail: https://example.com/{id}.png\n{yaml}");
+            let game = Game::build(
+                &data,
+                id.to_string(),
+                serde_yaml::from_str(&yaml)?,
+                PathBuf::from(id),
+            )?;
+            data.games.insert(id.to_string(), Rc::new(game));
+        }
+        data.post_load_games()?;
+        Ok(data)
+    }
+
+    fn relations(data: &ContextData, id: &str) -> Vec<(&'static str, String)> {
+        data.games[id]
+            .relations
+            .iter()
+            .map(|r| (r.kind.as_str(), r.target.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_relations_bidirectional() {
+        let data = load(&[
+            ("a", "relations: { sequel: [b], related: [c] }"),
+            ("b", "relations: { prequel: [a] }"),
+            ("c", ""),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            relations(&data, "a"),
+            [("sequel", "b".to_owned()), ("related", "c".to_owned())]
+        );
+        assert_eq!(relations(&data, "b"), [("prequel", "a".to_owned())]);
+        assert_eq!(relations(&data, "c"), [("related", "a".to_owned())]);
+    }
+
+    #[test]
+    fn test_relations_redirect() {
+        let data = load(&[
+            ("a", "relations: { dlc: [old-b] }"),
+            ("b", ""),
+            ("old-b", "state: redirected\nredirect-to: b"),
+        ])
+        .unwrap();
+
+        assert_eq!(relations(&data, "a"), [("dlc", "b".to_owned())]);
+        assert_eq!(relations(&data, "b"), [("base-game", "a".to_owned())]);
+        assert!(relations(&data, "old-b").is_empty());
+    }
+
+    #[test]
+    fn test_relations_invalid() {
+        let invalid = |games: &[(&str, &str)], message: &str| {
+            let err = load(games).err().unwrap().to_string();
+            assert!(err.contains(message), "{}", err);
+        };
+
+        invalid(
+            &[("a", "relations: { sequel: [b] }")],
+            "1 invalid game relations",
+        );
+        invalid(
+            &[("a", "relations: { sequel: [a] }")],
+            "can not relate to itself",
+        );
+        invalid(
+            &[
+                ("a", "relations: { related: [old-a] }"),
+                ("old-a", "state: redirected\nredirect-to: a"),
+            ],
+            "1 invalid game relations",
+        );
+        invalid(
+            &[
+                ("a", "relations: { sequel: [b] }"),
+                ("b", "state: expunged"),
+            ],
+            "1 invalid game relations",
+        );
+        // both games end up with the other as sequel and prequel
+        invalid(
+            &[
+                ("a", "relations: { sequel: [b] }"),
+                ("b", "relations: { sequel: [a] }"),
+            ],
+            "2 invalid game relations",
+        );
+    }
+
+    #[test]
+    fn test_exclude_sensitive() {
+        let mut fixture = Fixture::new("sfw");
+        assert_eq!(fixture.data.games["bar-game"].relations.len(), 2);
+
+        fixture.data.exclude_sensitive();
+
+        assert!(!fixture.data.games.contains_key("adult-game"));
+        assert!(fixture.data.games.contains_key("old-foo"));
+
+        let bar = &fixture.data.games["bar-game"];
+        let targets: Vec<_> = bar.relations.iter().map(|r| r.target.as_str()).collect();
+        assert_eq!(targets, ["foo-game"]);
+        assert!(bar.medias.is_empty());
+        assert_eq!(fixture.data.games["foo-game"].medias.len(), 3);
+    }
+
+    /// Rules written out of name order, with a fake table header in a string
+    const ORDERED_STOCK: &str = r#"
+[link.zeta]
+regex = "^x:(.*)$"
+www_href = "https://zeta.example.com/{{ _1 }}"
+inference = true
+icon = """
+[link.beta]
+"""
+
+[link.alpha]
+regex = "^x:(.*)$"
+www_href = "https://alpha.example.com/{{ _1 }}"
+inference = true
+
+[link.high]
+regex = "^x:1$"
+www_href = "https://high.example.com/"
+inference = true
+priority = 10
+
+[link.beta]
+regex = "^x:(.*)$"
+www_href = "https://beta.example.com/{{ _1 }}"
+inference = true
+"#;
+
+    fn inference_order(data: &ContextData) -> Vec<&str> {
+        data.link_rules
+            .inference_rules
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_stock_rule_order() {
+        let mut data = ContextData::default();
+        data.load_stock_str(ORDERED_STOCK, "ordered.toml").unwrap();
+        assert_eq!(inference_order(&data), ["high", "zeta", "alpha", "beta"]);
+
+        // a later config replaces the rule, which then comes after the others
+        let zeta = "[link.zeta]\nregex = '^x:(.*)$'\ninference = true\n";
+        data.load_stock_str(zeta, "override.toml").unwrap();
+        assert_eq!(inference_order(&data), ["high", "alpha", "beta", "zeta"]);
+
+        let bad = "[link.bad]\npriority = 'high'\n";
+        let e = data.load_stock_str(bad, "bad.toml").unwrap_err();
+        assert!(e.to_string().contains("1 invalid link rules in 'bad.toml'"));
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut data = ContextData::default();
+        data.load_stock_str(ORDERED_STOCK, "ordered.toml").unwrap();
+
+        assert_eq!(
+            data.link_rules.explain("x:1"),
+            [
+                "Inference rules, in the order they are tried:",
+                "  high (priority 10, order 2): regex '^x:1$': match, used",
+                "  zeta (priority 0, order 0): regex '^x:(.*)$': match, shadowed",
+                "  alpha (priority 0, order 1): regex '^x:(.*)$': match, shadowed",
+                "  beta (priority 0, order 3): regex '^x:(.*)$': match, shadowed",
+                "Reverse patterns, tried if no inference rule matches:",
+                "Resolved by rule 'high' to 'https://high.example.com/'",
+            ]
+        );
+        assert_eq!(
+            data.link_rules.explain("x:2")[1..5],
+            [
+                "  high (priority 10, order 2): regex '^x:1$': no match",
+                "  zeta (priority 0, order 0): regex '^x:(.*)$': match, used",
+                "  alpha (priority 0, order 1): regex '^x:(.*)$': match, shadowed",
+                "  beta (priority 0, order 3): regex '^x:(.*)$': match, shadowed",
+            ]
+        );
     }
 }
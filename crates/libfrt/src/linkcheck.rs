@@ -0,0 +1,439 @@
+//! Checker for the final URIs of links, used by `frt check-links`.
+//!
+//! Requests are made by `curl`, so both http and https links can be checked
+//! without bundling a TLS stack.
+//!
+//! Only links of games are checked. Author entries are not loaded yet
+//! (see `ContextData::load_author`) and have no links to collect.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::is_remote::is_remote;
+use crate::ContextData;
+
+/// A link to be checked, and where it comes from.
+#[derive(Debug, Clone)]
+pub struct LinkRef {
+    pub game: String,
+    /// Name of the stock rule the link is built by, `None` for custom links
+    pub rule: Option<String>,
+    pub uri: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkStatus {
+    /// Final HTTP status code is successful
+    Ok(u16),
+    /// Server answered with an error status code
+    Broken(u16),
+    /// Request failed, e.g. DNS error, connection refused or timeout
+    Failed(String),
+}
+
+impl LinkStatus {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, LinkStatus::Ok(_))
+    }
+}
+
+impl fmt::Display for LinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkStatus::Ok(code) => write!(f, "ok {code}"),
+            LinkStatus::Broken(code) => write!(f, "broken {code}"),
+            LinkStatus::Failed(reason) => write!(f, "failed: {reason}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LinkCheckOptions {
+    /// Max number of concurrent requests
+    pub jobs: usize,
+    /// Timeout of a single request, in seconds
+    pub timeout: u64,
+    /// Prefix replacements `(from, to)` applied to URIs before checking
+    pub base_urls: Vec<(String, String)>,
+    /// How long a successful result is reused, in seconds
+    pub max_age: i64,
+}
+
+impl Default for LinkCheckOptions {
+    fn default() -> Self {
+        Self {
+            jobs: 8,
+            timeout: 20,
+            base_urls: Vec::new(),
+            max_age: 3 * 24 * 3600,
+        }
+    }
+}
+
+impl LinkCheckOptions {
+    /// Apply the first matching base-URL override to `uri`.
+    pub fn rewrite(&self, uri: &str) -> String {
+        for (from, to) in self.base_urls.iter() {
+            if let Some(rest) = uri.strip_prefix(from.as_str()) {
+                return format!("{to}{rest}");
+            }
+        }
+
+        uri.to_owned()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CacheEntry {
+    status: LinkStatus,
+    checked: i64,
+}
+
+/// Results of previous runs, keyed by the checked URL.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct LinkCheckCache {
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl LinkCheckCache {
+    /// Load cache from `path`, a missing file results in an empty cache.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        match std::fs::read_to_string(path.as_ref()) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Cached status of `url`. Only successful results are reused, so fixed
+    /// links are noticed on the next run.
+    fn get(&self, url: &str, now: i64, max_age: i64) -> Option<&LinkStatus> {
+        self.entries
+            .get(url)
+            .filter(|e| e.status.is_ok() && now - e.checked < max_age)
+            .map(|e| &e.status)
+    }
+}
+
+pub struct LinkCheckResult {
+    pub link: LinkRef,
+    pub status: LinkStatus,
+    pub cached: bool,
+}
+
+pub struct LinkCheckReport {
+    pub results: Vec<LinkCheckResult>,
+}
+
+impl LinkCheckReport {
+    pub fn broken(&self) -> usize {
+        self.results.iter().filter(|r| !r.status.is_ok()).count()
+    }
+}
+
+impl fmt::Display for LinkCheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut by_game: BTreeMap<&str, Vec<&LinkCheckResult>> = BTreeMap::new();
+        let mut by_rule: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+
+        for r in self.results.iter() {
+            let rule = r.link.rule.as_deref().unwrap_or("(custom)");
+            let counter = by_rule.entry(rule).or_default();
+
+            if r.status.is_ok() {
+                counter.0 += 1;
+            } else {
+                counter.1 += 1;
+                by_game.entry(r.link.game.as_str()).or_default().push(r);
+            }
+        }
+
+        writeln!(f, "Broken links by game:")?;
+        if by_game.is_empty() {
+            writeln!(f, "  (none)")?;
+        }
+        for (game, results) in by_game.iter() {
+            writeln!(f, "  {game}")?;
+            for r in results.iter() {
+                writeln!(
+                    f,
+                    "    [{}] {} {}",
+                    r.link.rule.as_deref().unwrap_or("(custom)"),
+                    r.link.uri,
+                    r.status
+                )?;
+            }
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Summary by stock rule:")?;
+        for (rule, (ok, broken)) in by_rule.iter() {
+            writeln!(f, "  {rule}: {ok} ok, {broken} broken")?;
+        }
+
+        writeln!(
+            f,
+            "\n{} links checked, {} broken, {} from cache",
+            self.results.len(),
+            self.broken(),
+            self.results.iter().filter(|r| r.cached).count()
+        )
+    }
+}
+
+/// Collect remote links of every game which is still rendered. Authors are
+/// not covered, see the module documentation.
+pub fn collect_links(data: &ContextData) -> Vec<LinkRef> {
+    let mut games: Vec<_> = data
+        .games
+        .values()
+        .filter(|g| !g.state.is_tombstone())
+        .collect();
+    games.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+
+    let mut ret = Vec::new();
+
+    for game in games {
+        for link in game.links.iter() {
+            if !is_remote(&link.uri) {
+                debug!("Game '{}': skip non-http link '{}'", game.id, link.uri);
+                continue;
+            }
+
+            ret.push(LinkRef {
+                game: game.id.clone(),
+                rule: link.rule.as_ref().map(|r| r.name.clone()),
+                uri: link.uri.clone(),
+            });
+        }
+    }
+
+    ret
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn curl(url: &str, timeout: u64, head: bool) -> LinkStatus {
+    let mut cmd = Command::new("curl");
+    cmd.args(["-sS", "-L", "--max-redirs", "10", "-o", "/dev/null"])
+        .args(["-w", "%{http_code}", "-A", "frt-linkcheck"])
+        .arg("--max-time")
+        .arg(timeout.to_string());
+
+    if head {
+        cmd.arg("-I");
+    }
+
+    let output = match cmd.arg(url).output() {
+        Ok(output) => output,
+        Err(e) => return LinkStatus::Failed(format!("can not run curl: {e}")),
+    };
+
+    if !output.status.success() {
+        return LinkStatus::Failed(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+
+    match String::from_utf8_lossy(&output.stdout).trim().parse::<u16>() {
+        Ok(code) if (200..400).contains(&code) => LinkStatus::Ok(code),
+        Ok(code) => LinkStatus::Broken(code),
+        Err(_) => LinkStatus::Failed("invalid response".to_owned()),
+    }
+}
+
+/// Check a single URL. Some sites reject HEAD requests, so GET is tried
+/// before reporting an error status.
+pub fn check_url(url: &str, timeout: u64) -> LinkStatus {
+    match curl(url, timeout, true) {
+        LinkStatus::Broken(_) => curl(url, timeout, false),
+        status => status,
+    }
+}
+
+/// Check `links`, reusing and updating results in `cache`.
+pub fn check_links(
+    links: Vec<LinkRef>,
+    options: &LinkCheckOptions,
+    cache: &mut LinkCheckCache,
+) -> LinkCheckReport {
+    let now = now();
+
+    let mut queued = HashSet::new();
+    let mut queue = Vec::new();
+
+    for link in links.iter() {
+        let url = options.rewrite(&link.uri);
+        if cache.get(&url, now, options.max_age).is_none() && queued.insert(url.clone()) {
+            queue.push(url);
+        }
+    }
+
+    info!(
+        "Checking {} links ({} cached)",
+        queue.len(),
+        links.len() - queue.len()
+    );
+
+    let count = queue.len();
+    let queue = Arc::new(Mutex::new(queue));
+    let (tx, rx) = mpsc::channel();
+
+    let workers: Vec<_> = (0..options.jobs.max(1).min(count))
+        .map(|_| {
+            let queue = queue.clone();
+            let tx = tx.clone();
+            let timeout = options.timeout;
+
+            thread::spawn(move || loop {
+                let Some(url) = queue.lock().unwrap().pop() else {
+                    break;
+                };
+
+                let status = check_url(&url, timeout);
+                debug!("{url}: {status}");
+
+                if tx.send((url, status)).is_err() {
+                    break;
+                }
+            })
+        })
+        .collect();
+
+    drop(tx);
+
+    let mut fresh = HashMap::new();
+    for (url, status) in rx {
+        cache.entries.insert(
+            url.clone(),
+            CacheEntry {
+                status: status.clone(),
+                checked: now,
+            },
+        );
+        fresh.insert(url, status);
+    }
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    let results = links
+        .into_iter()
+        .map(|link| {
+            let url = options.rewrite(&link.uri);
+            let (status, cached) = match fresh.get(&url) {
+                Some(status) => (status.clone(), false),
+                None => match cache.get(&url, now, options.max_age) {
+                    Some(status) => (status.clone(), true),
+                    None => (LinkStatus::Failed("not checked".to_owned()), false),
+                },
+            };
+
+            LinkCheckResult {
+                link,
+                status,
+                cached,
+            }
+        })
+        .collect();
+
+    LinkCheckReport { results }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    use super::*;
+
+    /// Answers 200 for `/ok*` and 404 for anything else.
+    fn mock_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                reader.read_line(&mut line).unwrap();
+
+                while let Ok(n) = reader.read_line(&mut String::new()) {
+                    if n <= 2 {
+                        break;
+                    }
+                }
+
+                let path = line.split(' ').nth(1).unwrap_or_default();
+                let code = match path.starts_with("/ok") {
+                    true => "200 OK",
+                    false => "404 Not Found",
+                };
+
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {code}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+            }
+        });
+
+        format!("http://{addr}")
+    }
+
+    fn link(game: &str, rule: Option<&str>, uri: &str) -> LinkRef {
+        LinkRef {
+            game: game.to_owned(),
+            rule: rule.map(|s| s.to_owned()),
+            uri: uri.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_check_links() {
+        if Command::new("curl").arg("--version").output().is_err() {
+            eprintln!("curl is not available, skipped");
+            return;
+        }
+
+        let options = LinkCheckOptions {
+            base_urls: vec![("https://example.com".to_owned(), mock_server())],
+            ..Default::default()
+        };
+        let links = vec![
+            link("a", Some("steam"), "https://example.com/ok/1"),
+            link("a", None, "https://example.com/dead"),
+            link("b", Some("steam"), "https://example.com/ok/1"),
+        ];
+
+        let mut cache = LinkCheckCache::default();
+        let report = check_links(links.clone(), &options, &mut cache);
+
+        assert_eq!(report.broken(), 1);
+        assert_eq!(report.results[0].status, LinkStatus::Ok(200));
+        assert_eq!(report.results[1].status, LinkStatus::Broken(404));
+        assert!(report.to_string().contains("  a\n    [(custom)] https://example.com/dead"));
+
+        let report = check_links(links, &options, &mut cache);
+        assert!(report.results[0].cached && report.results[2].cached);
+        assert!(!report.results[1].cached);
+    }
+}
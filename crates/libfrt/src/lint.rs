@@ -0,0 +1,145 @@
+//! Checks of source data which do not fail the build, used by `frt lint`.
+
+use std::fmt;
+
+use crate::entries::media::{Image, Media};
+use crate::ContextData;
+
+#[derive(Debug)]
+pub struct LintMessage {
+    pub game: String,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for LintMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.game, self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n    suggestion: {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Lint all loaded games, ordered by game id.
+pub fn lint_games(data: &ContextData) -> Vec<LintMessage> {
+    let mut games: Vec<_> = data.games.values().collect();
+    games.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+
+    let mut ret = Vec::new();
+
+    for game in games {
+        for link in game.links.iter() {
+            if let Some(problem) = &link.hint_problem {
+                ret.push(LintMessage {
+                    game: game.id.clone(),
+                    message: problem.message.clone(),
+                    suggestion: problem.suggestion.as_ref().map(|s| format!("use '.{s}'")),
+                });
+            }
+
+            if let (Some(rewrite), Some(rule)) = (&link.rewrite, &link.rule) {
+                ret.push(LintMessage {
+                    game: game.id.clone(),
+                    message: format!(
+                        "link '{}' is recognized by stock rule '{}'",
+                        rewrite.from, rule.name
+                    ),
+                    suggestion: Some(format!("write it as '{}'", rewrite.to)),
+                });
+            }
+        }
+        for (i, gm) in game.medias.iter().enumerate() {
+            let images: Vec<&Image> = match &gm.media {
+                Media::Image(image) => vec![image],
+                Media::HBox(images) => images.iter().collect(),
+                _ => Vec::new(),
+            };
+
+            for image in images {
+                if image.alt.is_empty() {
+                    ret.push(LintMessage {
+                        game: game.id.clone(),
+                        message: format!("screenshot #{} '{}' has no alt text", i + 1, image.uri()),
+                        suggestion: Some("describe the image with 'alt'".to_owned()),
+                    });
+                }
+            }
+        }
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    use crate::entries::game::Game;
+    use crate::testing::STOCK;
+    use crate::ContextData;
+
+    #[test]
+    fn test_lint_alt_text() {
+        let raw = serde_yaml::from_str(
+            "name: Foo\n\
+             thumbnail: https://example.com/thumbnail.png\n\
+             screenshots:\n\
+             - uri: https://example.com/1.png\n  alt: Title screen\n\
+             - https://example.com/2.png\n\
+             - hbox:\n  - uri: https://example.com/3.png\n    alt: Left\n  \
+             - https://example.com/4.png\n",
+        )
+        .unwrap();
+
+        let mut data = ContextData::default();
+        let game = Game::build(&data, "foo".to_owned(), raw, PathBuf::new()).unwrap();
+        data.games.insert(game.id.clone(), Rc::new(game));
+
+        let messages: Vec<_> = super::lint_games(&data)
+            .iter()
+            .map(|m| m.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "foo: screenshot #2 'https://example.com/2.png' has no alt text\n    \
+                 suggestion: describe the image with 'alt'",
+                "foo: screenshot #3 'https://example.com/4.png' has no alt text\n    \
+                 suggestion: describe the image with 'alt'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_rewrite() {
+        let raw = serde_yaml::from_str(
+            "name: Foo\n\
+             thumbnail: https://example.com/thumbnail.png\n\
+             links:\n\
+             - https://store.steampowered.com/app/100\n\
+             - steam:200\n\
+             - name: Foo\n  uri: https://foo.example.com/\n",
+        )
+        .unwrap();
+
+        let mut data = ContextData::default();
+        data.load_stock_str(STOCK, "stock.toml").unwrap();
+        let game = Game::build(&data, "foo".to_owned(), raw, PathBuf::new()).unwrap();
+        data.games.insert(game.id.clone(), Rc::new(game));
+
+        let messages: Vec<_> = super::lint_games(&data)
+            .iter()
+            .map(|m| m.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "foo: link 'https://store.steampowered.com/app/100' is recognized by stock \
+                 rule 'steam'\n    suggestion: write it as 'steam:100'",
+            ]
+        );
+    }
+}
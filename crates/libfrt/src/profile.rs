@@ -4,6 +4,7 @@ use serde::Deserialize;
 use toml::Value;
 use anyhow::Result;
 
+use crate::entries::link::HintPolicy;
 use crate::utils;
 
 #[derive(Deserialize, Debug)]
@@ -16,6 +17,20 @@ pub struct Profile {
     pub path_games: Vec<String>,
     pub path_authors: Vec<String>,
 
+    /// Use git history for the added and updated time of bundles
+    pub git_timestamps: bool,
+
+    /// Leave out sensitive games and medias entirely, for safe-for-work builds
+    pub exclude_sensitive: bool,
+
+    /// How stock link hints with unknown rule names or mismatched URIs are
+    /// handled: `lenient`, `migration` or `strict`
+    pub stock_link_hints: HintPolicy,
+
+    /// Backends of `frt build` when none is selected on the command line,
+    /// in render order
+    pub build_backends: Vec<String>,
+
     pub backends: HashMap<String, Value>
 }
 
@@ -29,6 +44,14 @@ impl Default for Profile {
             path_games: vec![String::from("games")],
             path_authors: vec![String::from("authors")],
 
+            git_timestamps: true,
+
+            exclude_sensitive: false,
+
+            stock_link_hints: HintPolicy::default(),
+
+            build_backends: vec![String::from("www")],
+
             backends: HashMap::new(),
         }
     }
@@ -0,0 +1,222 @@
+//! Templates of new bundles, used by `frt new`.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_yaml::Value;
+
+use crate::entries::link::LinkRuleManager;
+
+lazy_static! {
+    static ref GAME_ID_RE: Regex = Regex::new(r"^[A-Za-z0-9][A-Za-z0-9_-]*$").unwrap();
+}
+
+/// Fail if `id` is not usable as a bundle directory name, or any of
+/// `path_games` already has a bundle of it
+pub fn check_game_id<S: AsRef<str>>(id: &str, path_games: &[S]) -> Result<()> {
+    if !GAME_ID_RE.is_match(id) {
+        crate::bail!(
+            InvalidArgument,
+            "Invalid game id '{}', use letters, digits, '-' and '_'",
+            id
+        )
+    }
+
+    for dir in path_games {
+        let bundle = Path::new(dir.as_ref()).join(id);
+        if bundle.exists() {
+            crate::bail!(
+                InvalidArgument,
+                "Game '{}' already exists: '{}'",
+                id,
+                bundle.display()
+            )
+        }
+    }
+
+    Ok(())
+}
+
+/// Quote `s` as a YAML scalar
+fn scalar(s: &str) -> Result<String> {
+    Ok(serde_yaml::to_string(s)?.trim_end().to_owned())
+}
+
+/// Values of a new game bundle, the rest of `game.yaml` is left as comments
+#[derive(Default, Debug)]
+pub struct GameTemplate {
+    pub name: String,
+    pub brief_description: Option<String>,
+    /// File name of the thumbnail in the bundle
+    pub thumbnail: Option<String>,
+    /// Items of `links`
+    pub links: Vec<Value>,
+}
+
+impl GameTemplate {
+    /// Add a link to `uri`, as stock link if an inference or reverse rule
+    /// recognizes it. Returns the name of the rule.
+    pub fn add_link(&mut self, rules: &LinkRuleManager, uri: &str) -> Result<Option<String>> {
+        let (rule, item) = match rules.stock_link_item(uri)? {
+            Some((rule, item)) => (Some(rule.name.clone()), item),
+            None => {
+                // label custom links with the host name
+                let host = uri.split_once("://").map(|(_, rest)| rest).unwrap_or(uri);
+                let host = host.split('/').next().unwrap_or(host);
+
+                let mut item = serde_yaml::Mapping::new();
+                item.insert("name".into(), host.into());
+                item.insert("uri".into(), uri.into());
+                (None, Value::Mapping(item))
+            }
+        };
+
+        self.links.push(item);
+        Ok(rule)
+    }
+
+    /// Content of `game.yaml`
+    pub fn render(&self) -> Result<String> {
+        let mut s = String::new();
+
+        s.push_str(&format!("name: {}\n\n", scalar(&self.name)?));
+
+        s.push_str("# 'plain' or 'markdown'\n");
+        s.push_str("description-format: markdown\n");
+        s.push_str("description: |\n  TODO: describe the game.\n\n");
+
+        s.push_str("# One line shown in lists, optional\n");
+        match &self.brief_description {
+            Some(brief) => s.push_str(&format!("brief-description: {}\n\n", scalar(brief)?)),
+            None => s.push_str("#brief-description: \n\n"),
+        }
+
+        match &self.thumbnail {
+            Some(thumbnail) => s.push_str(&format!(
+                "# Image file in this bundle, or URL\nthumbnail: {}\n\n",
+                scalar(thumbnail)?
+            )),
+            None => s.push_str(
+                "# TODO: put the image into this bundle, or use a URL\n\
+                 thumbnail: thumbnail.png\n\n",
+            ),
+        }
+
+        s.push_str(
+            "# 'general', 'mature' or 'adult'\n\
+             #rating: general\n\n\
+             # windows, macos, linux, android, ios, web, switch, playstation, xbox\n\
+             #platforms: [ windows ]\n\n\
+             # released, early-access, in-development or cancelled\n\
+             #release-status: released\n\
+             # YYYY, YYYY-MM or YYYY-MM-DD\n\
+             #release-date: 2024\n\n\
+             # Language tags of the game itself\n\
+             #languages: [ en ]\n\n\
+             # free, paid or donation\n\
+             #monetization: free\n\n\
+             #authors:\n\
+             #  - name: Someone\n\
+             #    role: [ developer ]\n\n",
+        );
+
+        s.push_str("# Stock links (e.g. steam:123) or { name, uri } custom links\n");
+        match self.links.is_empty() {
+            true => s.push_str("links: []\n\n"),
+            false => s.push_str(&format!(
+                "links:\n{}\n",
+                serde_yaml::to_string(&Value::Sequence(self.links.clone()))?
+            )),
+        }
+
+        s.push_str(
+            "# Image files or URLs, or { youtube: <id> }\n\
+             #screenshots:\n\
+             #  - screenshot1.png\n",
+        );
+
+        Ok(s)
+    }
+
+    /// Create the bundle of `id` in `dir`, copying `thumbnail` into it
+    pub fn create(&mut self, dir: &Path, id: &str, thumbnail: Option<&Path>) -> Result<PathBuf> {
+        let bundle = dir.join(id);
+        if bundle.exists() {
+            crate::bail!(InvalidArgument, "'{}' already exists", bundle.display())
+        }
+        std::fs::create_dir_all(&bundle)?;
+
+        if let Some(src) = thumbnail {
+            let name = match src.extension().and_then(|e| e.to_str()) {
+                Some(ext) => format!("thumbnail.{}", ext.to_lowercase()),
+                None => String::from("thumbnail"),
+            };
+            std::fs::copy(src, bundle.join(&name))?;
+            self.thumbnail = Some(name);
+        }
+
+        std::fs::write(bundle.join("game.yaml"), self.render()?)?;
+
+        Ok(bundle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_yaml::Value;
+
+    use crate::entries::raw::RawGame;
+    use crate::testing::STOCK;
+    use crate::ContextData;
+
+    #[test]
+    fn test_game_template() {
+        assert!(super::check_game_id("foo-game_2", &["games"]).is_ok());
+        assert!(super::check_game_id("../foo", &["games"]).is_err());
+        assert!(super::check_game_id("-foo", &["games"]).is_err());
+
+        let template = super::GameTemplate {
+            name: "Foo: the game".to_owned(),
+            brief_description: Some("#1".to_owned()),
+            links: vec!["steam:123".into()],
+            ..Default::default()
+        };
+
+        let raw: RawGame = serde_yaml::from_str(&template.render().unwrap()).unwrap();
+        assert_eq!(raw.name, "Foo: the game");
+        assert_eq!(raw.brief_description.as_deref(), Some("#1"));
+        assert_eq!(raw.thumbnail.as_deref(), Some("thumbnail.png"));
+        assert_eq!(raw.links.len(), 1);
+    }
+
+    #[test]
+    fn test_add_link() {
+        let mut data = ContextData::default();
+        data.load_stock_str(STOCK, "stock.toml").unwrap();
+
+        let mut template = super::GameTemplate::default();
+        let add = |template: &mut super::GameTemplate, uri| {
+            template.add_link(&data.link_rules, uri).unwrap()
+        };
+
+        assert_eq!(
+            add(&mut template, "https://store.steampowered.com/app/100").as_deref(),
+            Some("steam")
+        );
+        assert_eq!(
+            add(&mut template, "twitter:foo").as_deref(),
+            Some("twitter")
+        );
+        assert_eq!(add(&mut template, "https://foo.example.com/about"), None);
+
+        let custom: Value =
+            serde_yaml::from_str("{ name: foo.example.com, uri: 'https://foo.example.com/about' }")
+                .unwrap();
+        assert_eq!(
+            template.links,
+            [Value::from("steam:100"), Value::from("twitter:foo"), custom]
+        );
+    }
+}
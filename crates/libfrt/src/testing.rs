@@ -0,0 +1,281 @@
+//! A small site for the tests of libfrt and the backends, enabled by the
+//! `testing` feature.
+//!
+//! Games of the fixture:
+//!
+//! - `foo-game`: bundled thumbnail and screenshot, stock and custom links,
+//!   localized name for `zh-cn`
+//! - `bar-game`: remote images, sequel of `foo-game`
+//! - `hidden-game`: delisted
+//! - `gone-game`: expunged
+//! - `old-foo`: redirected to `foo-game`
+//! - `adult-game`: rated adult, related to `bar-game`, which also has a
+//!   sensitive screenshot
+
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+
+use crate::backend::{Backend, BackendArguments};
+use crate::entries::game::{Game, GameL10n};
+use crate::i18n::LangId;
+use crate::profile::Profile;
+use crate::ContextData;
+
+pub const STOCK: &str = r#"
+[link.website]
+category = "official"
+label = { _ = "Official website", zh-cn = "官方网站" }
+passthrough = true
+
+[link.steam]
+category = "store"
+icon = "site-steam"
+label = { _ = "Get on Steam", zh-cn = "在 Steam 上获取" }
+regex = "^steam:(?P<id>.*)$"
+www_href = "https://store.steampowered.com/app/{{ id }}"
+inference = true
+reverse = ['^https?://store\.steampowered\.com/app/(?P<id>[0-9]+)']
+canonical = "steam:{{ id }}"
+
+[link.twitter]
+category = "social"
+label = { _ = "Twitter: @{{ _1 }}" }
+regex = "^twitter:(.*)$"
+www_href = "https://twitter.com/{{ _1 }}"
+inference = true
+"#;
+
+pub const UI: &str = r#"
+[_]
+main.title = "Furry Games"
+relations.sequel = "Sequel"
+
+[_.facets.platform]
+linux = "Linux"
+windows = "Windows"
+
+[zh-cn]
+main.title = "兽人游戏"
+relations.sequel = "续作"
+
+[zh-cn.facets.platform]
+windows = "视窗"
+"#;
+
+/// A 1x1 PNG image
+pub const PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+    0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f,
+    0x15, 0xc4, 0x89, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x00,
+    0x01, 0x00, 0x00, 0x05, 0x00, 0x01, 0x0d, 0x0a, 0x2d, 0xb4, 0x00, 0x00, 0x00, 0x00, 0x49,
+    0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+];
+
+const GAMES: &[(&str, &str)] = &[
+    (
+        "foo-game",
+        r##"
+name: Foo Game
+description: "# Foo\n\nA game about **foo**."
+description-format: markdown
+brief-description: "Foo, the game"
+thumbnail: thumbnail.png
+platforms: [linux, windows]
+languages: [en]
+monetization: free
+authors:
+  - { name: Alice, role: [developer] }
+  - { name: Bob, role: [artist, music] }
+links:
+  - steam:100
+  - { name: .website, uri: "https://foo.example.com/" }
+  - { name: Patreon, uri: "https://patreon.example.com/foo" }
+screenshots:
+  - uri: screenshot.png
+    caption: { _: Title screen, zh-cn: 标题画面 }
+    alt: A fox in front of the title
+  - youtube: dQw4w9WgXcQ
+  - hbox:
+    - https://images.example.com/foo/1.png
+    - { uri: "https://images.example.com/foo/2.png", alt: Map }
+"##,
+    ),
+    (
+        "bar-game",
+        r##"
+name: Bar Game
+brief-description: "Sequel of Foo, with a ; semicolon"
+thumbnail: https://images.example.com/bar/thumbnail.png
+platforms: [windows]
+languages: [en, zh-cn]
+release-status: released
+authors:
+  - { name: Alice, role: [developer] }
+relations:
+  prequel: [foo-game]
+links:
+  - twitter:bargame
+screenshots:
+  - { uri: "https://images.example.com/bar/nsfw.png", sensitive: true }
+"##,
+    ),
+    (
+        "hidden-game",
+        r##"
+name: Hidden Game
+thumbnail: https://images.example.com/hidden/thumbnail.png
+state: delisted
+state-reason: Removed by the author
+platforms: [linux]
+"##,
+    ),
+    (
+        "gone-game",
+        r##"
+name: Gone Game
+state: expunged
+"##,
+    ),
+    (
+        "adult-game",
+        r##"
+name: Adult Game
+thumbnail: https://images.example.com/adult/thumbnail.png
+rating: adult
+relations:
+  related: [bar-game]
+"##,
+    ),
+    (
+        "old-foo",
+        r##"
+name: Old Foo
+state: redirected
+redirect-to: foo-game
+"##,
+    ),
+];
+
+/// Ids, names and URIs only used by the sensitive game and media
+const SENSITIVE: &[&str] = &["adult-game", "Adult Game", "/adult/", "nsfw.png"];
+
+/// All files under `dir`, recursively
+fn files(dir: &Path) -> Vec<PathBuf> {
+    let mut ret = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        match path.is_dir() {
+            true => ret.extend(files(&path)),
+            false => ret.push(path),
+        }
+    }
+    ret
+}
+
+/// A temporary directory with the site loaded from it, removed on drop
+pub struct Fixture {
+    pub root: PathBuf,
+    pub data: ContextData,
+}
+
+impl Fixture {
+    /// Load the fixture site into a new directory named after `name`
+    pub fn new(name: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let root = std::env::temp_dir().join(format!(
+            "frt-test-{}-{}-{}",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let data = load(&root).unwrap();
+        Self { root, data }
+    }
+
+    /// Directory for the output of backends, not created yet
+    pub fn output(&self) -> PathBuf {
+        self.root.join("output")
+    }
+
+    /// Resync and render `backend` into `output()`, as `frt build` does
+    pub fn render(&mut self, backend: &mut dyn Backend) -> BackendArguments {
+        let output = self.output();
+        self.render_to(backend, &output)
+    }
+
+    /// Same as `render`, into `output`
+    pub fn render_to(&mut self, backend: &mut dyn Backend, output: &Path) -> BackendArguments {
+        let mut args = BackendArguments::default();
+        args.set_bool("fs_output".to_owned(), true);
+        args.set_string("output".to_owned(), output.display().to_string());
+
+        let profile = Profile::default();
+        backend.resync(&profile, &mut self.data, &args).unwrap();
+        backend.render(&profile, &self.data).unwrap()
+    }
+
+    /// Fail if any file under `output()` mentions the sensitive game or
+    /// media, by name or content. For outputs rendered after
+    /// `ContextData::exclude_sensitive`.
+    pub fn assert_sensitive_excluded(&self) {
+        let files = files(&self.output());
+        assert!(!files.is_empty());
+
+        for path in files {
+            let content = std::fs::read(&path).unwrap();
+            let content = String::from_utf8_lossy(&content);
+            for needle in SENSITIVE {
+                assert!(
+                    !path.to_string_lossy().contains(needle) && !content.contains(needle),
+                    "'{}' found in '{}'",
+                    needle,
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+fn load(root: &Path) -> Result<ContextData> {
+    let mut data = ContextData::default();
+    data.load_stock_str(STOCK, "stock.toml")?;
+    data.load_ui_str(UI)?;
+    data.post_load_ui()?;
+
+    for (id, yaml) in GAMES {
+        let bundle = root.join("games").join(id);
+        std::fs::create_dir_all(&bundle)?;
+        std::fs::write(bundle.join("game.yaml"), yaml)?;
+        if *id == "foo-game" {
+            std::fs::write(bundle.join("thumbnail.png"), PNG)?;
+            std::fs::write(bundle.join("screenshot.png"), PNG)?;
+        }
+
+        let mut game = Game::build(&data, id.to_string(), serde_yaml::from_str(yaml)?, bundle)?;
+        if *id == "foo-game" {
+            game.l10n.insert(
+                LangId::from_tag("zh-cn").unwrap(),
+                GameL10n {
+                    name: Some("富游戏".to_owned()),
+                    description: None,
+                    brief_description: Some("关于富的游戏".to_owned()),
+                },
+            );
+        }
+        data.games.insert(game.id.clone(), Rc::new(game));
+    }
+
+    data.post_load_games()?;
+    Ok(data)
+}
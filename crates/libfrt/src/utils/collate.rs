@@ -0,0 +1,158 @@
+use std::cmp::Ordering;
+
+use crate::i18n::LangId;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Element {
+    /// Digit run, compared by value (length of the run without leading zeros first)
+    Number(usize, String),
+    Char(char),
+}
+
+/// Sort key for locale-aware string ordering.
+///
+/// The primary level ignores case, diacritics, punctuation and whitespace,
+/// compares digit runs numerically and applies per-language folding.
+/// Ties are broken by the original string.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct CollationKey {
+    primary: Vec<Element>,
+    orig: String,
+}
+
+fn fold_latin(c: char) -> &'static str {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' => "a",
+        'ç' | 'č' => "c",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' => "e",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' => "i",
+        'ñ' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' => "o",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' => "u",
+        'ý' | 'ÿ' => "y",
+        'š' => "s",
+        'ž' => "z",
+        'ß' => "ss",
+        'æ' => "ae",
+        'œ' => "oe",
+        _ => "",
+    }
+}
+
+fn fold(c: char, lang: LangId) -> char {
+    let c = match c as u32 {
+        // Fullwidth ASCII variants
+        0xff01..=0xff5e => char::from_u32(c as u32 - 0xfee0).unwrap_or(c),
+        // Katakana sorts together with hiragana in Japanese
+        0x30a1..=0x30f6 if lang == LangId::JaJp => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    };
+
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn strip_article(s: &str, lang: LangId) -> &str {
+    if lang != LangId::EnUs {
+        return s;
+    }
+
+    for article in ["the ", "a ", "an "] {
+        if s.len() > article.len()
+            && s.is_char_boundary(article.len())
+            && s[..article.len()].eq_ignore_ascii_case(article)
+        {
+            return &s[article.len()..];
+        }
+    }
+
+    s
+}
+
+impl CollationKey {
+    pub fn new(s: &str, lang: LangId) -> Self {
+        let mut primary = Vec::new();
+        let mut digits = String::new();
+
+        let flush = |digits: &mut String, primary: &mut Vec<Element>| {
+            if !digits.is_empty() {
+                let trimmed = digits.trim_start_matches('0').to_owned();
+                primary.push(Element::Number(trimmed.len(), trimmed));
+                digits.clear();
+            }
+        };
+
+        for c in strip_article(s.trim_start(), lang).chars() {
+            let c = fold(c, lang);
+
+            if c.is_ascii_digit() {
+                digits.push(c);
+                continue;
+            }
+
+            flush(&mut digits, &mut primary);
+
+            if !c.is_alphanumeric() {
+                continue;
+            }
+
+            match fold_latin(c) {
+                "" => primary.push(Element::Char(c)),
+                s => primary.extend(s.chars().map(Element::Char)),
+            }
+        }
+
+        flush(&mut digits, &mut primary);
+
+        Self {
+            primary,
+            orig: s.to_owned(),
+        }
+    }
+}
+
+/// Compare two strings with the collation rules of `lang`.
+pub fn compare(a: &str, b: &str, lang: LangId) -> Ordering {
+    CollationKey::new(a, lang).cmp(&CollationKey::new(b, lang))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::i18n::LangId;
+
+    fn sorted(mut v: Vec<&str>, lang: LangId) -> Vec<&str> {
+        v.sort_by(|a, b| super::compare(a, b, lang));
+        v
+    }
+
+    #[test]
+    fn test_collate() {
+        assert_eq!(
+            sorted(vec!["b", "f", "B", "a", "É", "c"], LangId::EnUs),
+            vec!["a", "B", "b", "c", "É", "f"]
+        );
+        assert_eq!(
+            sorted(vec!["Game 10", "Game 2", "game 1"], LangId::EnUs),
+            vec!["game 1", "Game 2", "Game 10"]
+        );
+        assert_eq!(
+            sorted(vec!["The Zoo", "Apple", "A Bear"], LangId::EnUs),
+            vec!["Apple", "A Bear", "The Zoo"]
+        );
+        assert_eq!(
+            sorted(vec!["The Zoo", "Tiger"], LangId::EnUs),
+            vec!["Tiger", "The Zoo"]
+        );
+        assert_eq!(
+            sorted(vec!["Tiger", "The Zoo"], LangId::ZhCn),
+            vec!["The Zoo", "Tiger"]
+        );
+        assert_eq!(
+            sorted(vec!["ケモノ", "かもめ", "きつね"], LangId::JaJp),
+            vec!["かもめ", "きつね", "ケモノ"]
+        );
+        assert_eq!(
+            sorted(vec!["Ｚｅｔａ", "alpha"], LangId::EnUs),
+            vec!["alpha", "Ｚｅｔａ"]
+        );
+    }
+}
@@ -0,0 +1,38 @@
+/// Format a unix timestamp as an UTC `YYYY-MM-DD` date.
+pub fn format_date(timestamp: i64) -> String {
+    // Days to civil date, after Howard Hinnant's `civil_from_days`
+    let z = timestamp.div_euclid(86400) + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Number of days of `month` (1-12) in `year` of the Gregorian calendar
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_format_date() {
+        assert_eq!(super::format_date(0), "1970-01-01");
+        assert_eq!(super::format_date(951782400), "2000-02-29");
+        assert_eq!(super::format_date(1792394989), "2026-10-19");
+        assert_eq!(super::format_date(-1), "1969-12-31");
+    }
+}
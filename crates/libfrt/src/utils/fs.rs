@@ -28,6 +28,13 @@ pub fn get_mtime<U: AsRef<Path>>(f: U) -> Result<u64> {
         .as_secs())
 }
 
+pub fn get_ctime<U: AsRef<Path>>(f: U) -> Result<u64> {
+    Ok(std::fs::metadata(f)?
+        .created()?
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs())
+}
+
 pub fn make_dir<U: AsRef<Path>>(p: U) -> Result<()> {
     let p = p.as_ref();
 
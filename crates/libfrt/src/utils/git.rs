@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Result;
+
+/// Collect commit timestamps for every top-level entry of `dir`.
+///
+/// Returns a map from entry name (e.g. a bundle directory name) to the
+/// timestamps of the first and the last commit touching any file below it.
+pub fn entry_timestamps<P: AsRef<Path>>(dir: P) -> Result<HashMap<String, (i64, i64)>> {
+    let cmd = Command::new("git")
+        .arg("-C")
+        .arg(dir.as_ref())
+        .args(["log", "--relative", "--format=%x00%at", "--name-only", "--", "."])
+        .output()?;
+
+    if !cmd.status.success() {
+        crate::bail!(
+            Other,
+            "git log failed in '{}': {}",
+            dir.as_ref().display(),
+            String::from_utf8_lossy(&cmd.stderr).trim()
+        )
+    }
+
+    let mut result: HashMap<String, (i64, i64)> = HashMap::new();
+    let mut timestamp = None;
+
+    for line in String::from_utf8_lossy(&cmd.stdout).lines() {
+        if let Some(ts) = line.strip_prefix('\0') {
+            timestamp = Some(ts.parse::<i64>()?);
+            continue;
+        }
+
+        let (Some(ts), Some(entry)) = (timestamp, line.split('/').next()) else {
+            continue;
+        };
+
+        if entry.is_empty() {
+            continue;
+        }
+
+        let v = result.entry(entry.to_owned()).or_insert((ts, ts));
+        v.0 = v.0.min(ts);
+        v.1 = v.1.max(ts);
+    }
+
+    Ok(result)
+}
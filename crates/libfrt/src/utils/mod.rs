@@ -1,5 +1,9 @@
+pub mod collate;
 pub mod cwebp;
+pub mod date;
 pub mod fs;
+pub mod git;
 pub mod is_remote;
+pub mod strsim;
 pub mod tengine;
 pub mod toml;
@@ -0,0 +1,52 @@
+/// Edit distance between two strings, counted in chars.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = match ca == *cb {
+                true => prev,
+                false => 1 + prev.min(cur).min(row[j]),
+            };
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// The candidate closest to `s`, if it is similar enough to be a typo.
+pub fn closest<'a, I>(s: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let max = (s.chars().count() / 3).max(2);
+
+    candidates
+        .into_iter()
+        .map(|c| (levenshtein(s, c), c))
+        .filter(|(d, _)| *d <= max)
+        .min()
+        .map(|(_, c)| c)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(super::levenshtein("steam", "steam"), 0);
+        assert_eq!(super::levenshtein("stem", "steam"), 1);
+        assert_eq!(super::levenshtein("kitten", "sitting"), 3);
+        assert_eq!(super::levenshtein("", "itch"), 4);
+
+        let names = ["steam", "steam-demo", "itch", "twitter"];
+        assert_eq!(super::closest("stema", names), Some("steam"));
+        assert_eq!(super::closest("itchio", names), Some("itch"));
+        assert_eq!(super::closest("gog", names), None);
+    }
+}
@@ -1,56 +1,434 @@
-use std::{borrow::Cow, collections::HashMap};
+//! A tiny template language for stock link labels and hrefs.
+//!
+//! - `{{ name }}` substitutes a variable
+//! - `{{ name | lower | urlencode }}` applies filters from left to right,
+//!   available filters are `lower`, `upper`, `urlencode` and `default("...")`
+//! - `{% if name %}...{% else %}...{% endif %}` renders a branch depending on
+//!   whether the variable is non-empty, `{% if not name %}` negates it
+//!
+//! Templates are parsed once by `Template::parse` and rendered many times.
+
+use std::collections::HashMap;
 
 use anyhow::Result;
-use regex::Regex;
 
-pub fn simple_template_render(template: &str, context: &HashMap<String, String>) -> Result<String> {
-    let mut s = template.to_owned();
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    Lower,
+    Upper,
+    UrlEncode,
+    /// Used when the value is empty
+    Default(String),
+}
+
+impl Filter {
+    fn apply(&self, value: String) -> String {
+        match self {
+            Filter::Lower => value.to_lowercase(),
+            Filter::Upper => value.to_uppercase(),
+            Filter::UrlEncode => urlencode(&value),
+            Filter::Default(default) if value.is_empty() => default.clone(),
+            Filter::Default(_) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Text(String),
+    Var {
+        name: String,
+        filters: Vec<Filter>,
+    },
+    If {
+        name: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// Percent-encode everything except unreserved characters of RFC 3986.
+pub fn urlencode(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                ret.push(b as char)
+            }
+            _ => ret.push_str(&format!("%{:02X}", b)),
+        }
+    }
+
+    ret
+}
+
+fn parse_name(s: &str) -> Result<String> {
+    let s = s.trim();
+    let mut chars = s.chars();
+
+    let valid = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !valid {
+        crate::bail!(InvalidArgument, "Invalid template variable name: '{}'", s)
+    }
+
+    Ok(s.to_owned())
+}
+
+fn parse_filter(s: &str) -> Result<Filter> {
+    let s = s.trim();
+
+    if let Some(arg) = s.strip_prefix("default(").and_then(|a| a.strip_suffix(')')) {
+        let arg = arg.trim();
+        let quoted = arg.len() >= 2
+            && ((arg.starts_with('"') && arg.ends_with('"'))
+                || (arg.starts_with('\'') && arg.ends_with('\'')));
+
+        if !quoted {
+            crate::bail!(
+                InvalidArgument,
+                "Argument of 'default' must be quoted: '{}'",
+                s
+            )
+        }
+
+        return Ok(Filter::Default(arg[1..arg.len() - 1].to_owned()));
+    }
+
+    Ok(match s {
+        "lower" => Filter::Lower,
+        "upper" => Filter::Upper,
+        "urlencode" => Filter::UrlEncode,
+        _ => crate::bail!(InvalidArgument, "Unknown template filter: '{}'", s),
+    })
+}
+
+/// Split an expression on `|`, ignoring the ones inside quotes.
+fn split_pipes(s: &str) -> Vec<&str> {
+    let mut ret = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '|') => {
+                ret.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    ret.push(&s[start..]);
+    ret
+}
+
+enum Token<'a> {
+    Text(&'a str),
+    Expr(&'a str),
+    Tag(&'a str),
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token<'_>>> {
+    let mut ret = Vec::new();
+    let mut rest = s;
+
+    while !rest.is_empty() {
+        let next = [rest.find("{{"), rest.find("{%")]
+            .into_iter()
+            .flatten()
+            .min();
+
+        let Some(start) = next else {
+            ret.push(Token::Text(rest));
+            break;
+        };
+
+        if start > 0 {
+            ret.push(Token::Text(&rest[..start]));
+        }
+
+        let close = match &rest[start..start + 2] {
+            "{{" => "}}",
+            _ => "%}",
+        };
+        let end = rest[start + 2..].find(close).ok_or_else(|| {
+            crate::err!(
+                InvalidArgument,
+                "Unclosed '{}' in template '{}'",
+                &rest[start..start + 2],
+                s
+            )
+        })? + start
+            + 2;
+
+        let inner = &rest[start + 2..end];
+        ret.push(match close {
+            "}}" => Token::Expr(inner),
+            _ => Token::Tag(inner),
+        });
+
+        rest = &rest[end + 2..];
+    }
+
+    Ok(ret)
+}
+
+/// An `if` block being parsed
+struct Frame {
+    name: String,
+    negate: bool,
+    /// Nodes before the block
+    outer: Vec<Node>,
+    /// Nodes of the first branch, once `else` is seen
+    then: Option<Vec<Node>>,
+}
+
+impl Template {
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut nodes = Vec::new();
+
+        for token in tokenize(s)? {
+            match token {
+                Token::Text(text) => nodes.push(Node::Text(text.to_owned())),
+                Token::Expr(expr) => {
+                    let mut parts = split_pipes(expr).into_iter();
+                    let name = parse_name(parts.next().unwrap_or_default())?;
+                    let filters = parts.map(parse_filter).collect::<Result<_>>()?;
+                    nodes.push(Node::Var { name, filters });
+                }
+                Token::Tag(tag) => {
+                    let words: Vec<&str> = tag.split_whitespace().collect();
+
+                    match words.as_slice() {
+                        ["if", name] | ["if", "not", name] => stack.push(Frame {
+                            name: parse_name(name)?,
+                            negate: words.len() == 3,
+                            outer: std::mem::take(&mut nodes),
+                            then: None,
+                        }),
+                        ["else"] => match stack.last_mut() {
+                            Some(frame) if frame.then.is_none() => {
+                                frame.then = Some(std::mem::take(&mut nodes));
+                            }
+                            _ => crate::bail!(
+                                InvalidArgument,
+                                "Unexpected 'else' in template '{}'",
+                                s
+                            ),
+                        },
+                        ["endif"] => {
+                            let frame = stack.pop().ok_or_else(|| {
+                                crate::err!(
+                                    InvalidArgument,
+                                    "Unexpected 'endif' in template '{}'",
+                                    s
+                                )
+                            })?;
 
-    for (k, v) in context.iter() {
-        let regex = Regex::new(format!("\\{{\\{{ *{} *\\}}\\}}", k).as_str())?;
-        let result = regex.replace_all(&s, v);
-        if let Cow::Owned(_) = result {
-            s = result.into_owned();
+                            let branch = std::mem::replace(&mut nodes, frame.outer);
+                            let (then, otherwise) = match frame.then {
+                                Some(then) => (then, branch),
+                                None => (branch, Vec::new()),
+                            };
+
+                            nodes.push(Node::If {
+                                name: frame.name,
+                                negate: frame.negate,
+                                then,
+                                otherwise,
+                            });
+                        }
+                        _ => crate::bail!(
+                            InvalidArgument,
+                            "Unknown template tag '{}' in '{}'",
+                            tag.trim(),
+                            s
+                        ),
+                    }
+                }
+            }
+        }
+
+        if !stack.is_empty() {
+            crate::bail!(InvalidArgument, "Unclosed 'if' in template '{}'", s)
         }
+
+        Ok(Self { nodes })
     }
 
-    Ok(s)
+    /// Names of all variables used by the template, sorted and deduplicated
+    pub fn variables(&self) -> Vec<&str> {
+        fn walk<'a>(nodes: &'a [Node], ret: &mut Vec<&'a str>) {
+            for node in nodes {
+                match node {
+                    Node::Text(_) => {}
+                    Node::Var { name, .. } => ret.push(name),
+                    Node::If {
+                        name,
+                        then,
+                        otherwise,
+                        ..
+                    } => {
+                        ret.push(name);
+                        walk(then, ret);
+                        walk(otherwise, ret);
+                    }
+                }
+            }
+        }
+
+        let mut ret = Vec::new();
+        walk(&self.nodes, &mut ret);
+        ret.sort_unstable();
+        ret.dedup();
+        ret
+    }
+
+    /// Fail if the template uses a variable not in `known`
+    pub fn check_variables(&self, known: &[String]) -> Result<()> {
+        for name in self.variables() {
+            if !known.iter().any(|k| k == name) {
+                crate::bail!(NotExist, "Unknown template variable '{}'", name)
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn render(&self, context: &HashMap<String, String>) -> Result<String> {
+        let mut ret = String::new();
+        Self::render_nodes(&self.nodes, context, &mut ret)?;
+        Ok(ret)
+    }
+
+    fn render_nodes(
+        nodes: &[Node],
+        context: &HashMap<String, String>,
+        ret: &mut String,
+    ) -> Result<()> {
+        let get = |name: &str| {
+            context
+                .get(name)
+                .ok_or_else(|| crate::err!(NotExist, "Unknown template variable '{}'", name))
+        };
+
+        for node in nodes {
+            match node {
+                Node::Text(text) => ret.push_str(text),
+                Node::Var { name, filters } => {
+                    let value = filters
+                        .iter()
+                        .fold(get(name)?.clone(), |value, f| f.apply(value));
+                    ret.push_str(&value);
+                }
+                Node::If {
+                    name,
+                    negate,
+                    then,
+                    otherwise,
+                } => {
+                    let branch = match get(name)?.is_empty() == *negate {
+                        true => then,
+                        false => otherwise,
+                    };
+                    Self::render_nodes(branch, context, ret)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
+    use super::Template;
+
+    fn render(template: &str, context: &HashMap<String, String>) -> String {
+        Template::parse(template).unwrap().render(context).unwrap()
+    }
+
     #[test]
-    fn test_simple_template_render() {
+    fn test_template_render() {
         let context = HashMap::from([
             (String::from("_1"), String::from("aaa")),
             (String::from("_2"), String::from("bbb")),
             (String::from("_3"), String::from("ccc")),
             (String::from("vv"), String::from("ddd")),
             (String::from("bb"), String::from("eee")),
+            (String::from("q"), String::from("Foo Bar/1")),
+            (String::from("empty"), String::from("")),
         ]);
 
         assert_eq!(
-            super::simple_template_render("http://example.com/xxx", &context).unwrap(),
+            render("http://example.com/xxx", &context),
             "http://example.com/xxx"
         );
         assert_eq!(
-            super::simple_template_render("http://example.com/{{ vv }}", &context).unwrap(),
+            render("http://example.com/{{ vv }}", &context),
             "http://example.com/ddd"
         );
         assert_eq!(
-            super::simple_template_render("http://example.com/{{ _1 }}/{{_2}}", &context).unwrap(),
+            render("http://example.com/{{ _1 }}/{{_2}}", &context),
             "http://example.com/aaa/bbb"
         );
         assert_eq!(
-            super::simple_template_render("http://example.com/{{ vv }}/{{bb}}", &context).unwrap(),
+            render("http://example.com/{{ vv }}/{{bb}}", &context),
             "http://example.com/ddd/eee"
         );
         assert_eq!(
-            super::simple_template_render("http://example.com/{{ _3 }}/{{ bb }}", &context)
-                .unwrap(),
+            render("http://example.com/{{ _3 }}/{{ bb }}", &context),
             "http://example.com/ccc/eee"
         );
+        assert_eq!(
+            render("?q={{ q | lower | urlencode }}", &context),
+            "?q=foo%20bar%2F1"
+        );
+        assert_eq!(
+            render("{{ empty | default(\"a|b\") | upper }}", &context),
+            "A|B"
+        );
+        assert_eq!(
+            render(
+                "{% if empty %}x{% else %}{% if not empty %}y{{ vv }}{% endif %}{% endif %}z",
+                &context
+            ),
+            "ydddz"
+        );
+        assert_eq!(
+            Template::parse("{% if vv %}{{ q }}{% endif %}{{ _1 }}")
+                .unwrap()
+                .variables(),
+            vec!["_1", "q", "vv"]
+        );
+    }
+
+    #[test]
+    fn test_template_errors() {
+        assert!(Template::parse("{{ vv").is_err());
+        assert!(Template::parse("{{ vv | nope }}").is_err());
+        assert!(Template::parse("{{ vv | default(x) }}").is_err());
+        assert!(Template::parse("{% if vv %}").is_err());
+        assert!(Template::parse("{% endif %}").is_err());
+        assert!(Template::parse("{% if vv %}{% else %}{% else %}{% endif %}").is_err());
+        assert!(Template::parse("{{ 1x }}").is_err());
+        assert!(Template::parse("{{ nope }}")
+            .unwrap()
+            .render(&HashMap::new())
+            .is_err());
     }
 }
@@ -28,6 +28,26 @@ window.make_picture = (data, _prefix, picture) => {
 	return picture;
 }
 
+document.addEventListener("click", e => {
+	if (e.target.classList.contains("sensitive_reveal")) {
+		const box = e.target.parentNode;
+		for (const template of box.querySelectorAll(":scope > template"))
+			template.replaceWith(template.content);
+		box.classList.add("revealed");
+	}
+
+	const load = e.target.closest(".youtube_load");
+	if (load) {
+		const facade = load.parentNode;
+		const iframe = document.createElement("iframe");
+		iframe.src = "https://www.youtube-nocookie.com/embed/{0}?autoplay=1".format(encodeURIComponent(facade.dataset.youtube));
+		iframe.setAttribute("allow", "autoplay; encrypted-media; picture-in-picture");
+		iframe.setAttribute("allowfullscreen", "");
+		facade.replaceChildren(iframe);
+		facade.classList.add("loaded");
+	}
+});
+
 if ('serviceWorker' in navigator)
 	navigator.serviceWorker.register('/sw.js');
 